@@ -19,7 +19,7 @@ pub struct ScriptAsset {
 
 /// On asset changer
 fn update_asset(ctx: &ReducerContext, asset: AssetFile) {
-    let Some(format) = asset.path.split('.').last() else { return };
+    let Some(format) = asset.path.split('.').next_back() else { return };
 
     match format {
         "rn" => {
@@ -31,6 +31,18 @@ fn update_asset(ctx: &ReducerContext, asset: AssetFile) {
             }
         },
 
+        "structure" => {
+            if let Err(e) = shared::worldgen::insert_structure(&asset.path, &asset.value) {
+                log::error!("Structure {} error: {}", asset.path, e);
+            }
+        },
+
+        "carving" => {
+            if let Err(e) = shared::worldgen::set_carving(&asset.value) {
+                log::error!("Carving {} error: {}", asset.path, e);
+            }
+        },
+
         // TODO: other formats
         _ => ()
     }
@@ -38,7 +50,7 @@ fn update_asset(ctx: &ReducerContext, asset: AssetFile) {
 
 /// On asset changer
 fn remove_asset(ctx: &ReducerContext, path: String) {
-    let Some(format) = path.split('.').last() else { return };
+    let Some(format) = path.split('.').next_back() else { return };
 
     match format {
         "rn" => {
@@ -47,6 +59,10 @@ fn remove_asset(ctx: &ReducerContext, path: String) {
             shared::remove_script(&path);
         },
 
+        "structure" => shared::worldgen::remove_structure(&path),
+
+        "carving" => shared::worldgen::reset_carving(),
+
         // TODO: other formats
         _ => ()
     }
@@ -65,6 +81,7 @@ pub fn add_raw_asset(ctx: &ReducerContext, path: String, value: Vec<u8>) {
         false => ctx.db.assets().path().update(asset)
     };
 
+    shared::events::emit(shared::events::Event::AssetChanged(asset.path.clone()));
     update_asset(ctx, asset);
 }
 
@@ -0,0 +1,74 @@
+use std::collections::*;
+use spacetimedb::{ReducerContext, Table};
+use shared::math::IVec3;
+use shared::residency::StoredChunk;
+
+use crate::{chunk, ChunkData};
+
+fn key(world: &str, pos: IVec3) -> String {
+    format!("{}:{}:{}:{}", world, pos.x, pos.y, pos.z)
+}
+
+/// Register stored chunks of all worlds, they are loaded instead of generation
+pub fn init(ctx: &ReducerContext) {
+    let mut worlds: BTreeMap<String, Vec<IVec3>> = BTreeMap::new();
+    for row in ctx.db.chunk().iter() {
+        worlds.entry(row.world).or_default().push(IVec3::new(row.px, row.py, row.pz));
+    }
+
+    for (world, positions) in worlds {
+        shared::residency::add_persisted(&world, positions);
+    }
+
+    shared::residency::set_loader(load);
+}
+
+fn stored(world: String, pos: IVec3, row: ChunkData) -> StoredChunk {
+    StoredChunk { world, pos, data: row.data, biomes: row.biomes, stage: row.stage }
+}
+
+/// Read stored chunk for scripts. They run inside of a reducer transaction only,
+/// and table access doesn't depend on the context
+fn load(world: &str, pos: IVec3) -> Option<StoredChunk> {
+    let ctx = ReducerContext::__dummy();
+    let row = ctx.db.chunk().key().find(key(world, pos))?;
+
+    Some(stored(world.to_string(), pos, row))
+}
+
+/// Insert or replace stored chunk row
+pub fn store(ctx: &ReducerContext, stored: StoredChunk) {
+    let row = ChunkData {
+        key: key(&stored.world, stored.pos),
+        world: stored.world,
+        px: stored.pos.x,
+        py: stored.pos.y,
+        pz: stored.pos.z,
+        data: stored.data,
+        biomes: stored.biomes,
+        stage: stored.stage,
+    };
+
+    match ctx.db.chunk().key().find(&row.key).is_none() {
+        true => ctx.db.chunk().insert(row),
+        false => ctx.db.chunk().key().update(row)
+    };
+}
+
+/// Write changed and evicted chunks with their biome maps and load requested ones
+pub fn flush(ctx: &ReducerContext) {
+    for stored in shared::residency::take_dirty() {
+        store(ctx, stored);
+    }
+
+    for stored in shared::residency::take_stored() {
+        store(ctx, stored);
+    }
+
+    for (world, pos) in shared::residency::take_loads() {
+        match ctx.db.chunk().key().find(key(&world, pos)) {
+            Some(row) => shared::residency::restore(stored(world, pos, row)),
+            None => shared::residency::missing(&world, pos)
+        }
+    }
+}
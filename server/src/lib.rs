@@ -1,29 +1,45 @@
 mod assets;
+mod chunks;
+mod metrics;
+mod regions;
+mod store;
+mod world;
 
-use spacetimedb::{Identity, ReducerContext, ScheduleAt, Table, TimeDuration};
+use spacetimedb::{Identity, ReducerContext, ScheduleAt, Table};
 
 // Once second in micros
 const DURATION: i64 = 1_000_000;
-const TPS: i64 = 30;
-const TICK: i64 = DURATION / TPS;
 
 pub fn get_player(ctx: &ReducerContext) -> Option<Player> {
     ctx.db.player().identity().find(ctx.sender)
 }
 
+/// Reducer caller must be admin
+fn require_admin(ctx: &ReducerContext) -> Result<(), String> {
+    match get_player(ctx).is_some_and(|p| p.is_admin) {
+        true => Ok(()),
+        false => Err(String::from("Only admin can do it"))
+    }
+}
+
 #[spacetimedb::table(name = player)]
 pub struct Player {
     #[primary_key]
     identity: Identity,
     is_admin: bool,
+    /// Current world id
+    world: String,
 }
 
-#[spacetimedb::table(name=chunk)]
+#[spacetimedb::table(name=chunk, public)]
 pub struct ChunkData {
     #[primary_key]
-    /// Current position formated position key
+    /// World and position formated key
     key: String,
 
+    #[index(btree)]
+    world: String,
+
     #[index(btree)]
     px: i32,
     #[index(btree)]
@@ -32,12 +48,19 @@ pub struct ChunkData {
     pz: i32,
 
     /// Raw chunk's data
-    data: Vec<u8>
+    data: Vec<u8>,
+    /// Columns biomes ids (index: x + z * SIZE)
+    biomes: Vec<u16>,
+    /// Last done generation stage
+    stage: u8
 }
 
 /// Setup core values and tables
 fn setup(ctx: &ReducerContext) {
     shared::init();
+    world::init(ctx);
+    store::init(ctx);
+    chunks::init(ctx);
 
     // Init assets (after Core initialization!)
     assets::init(ctx);
@@ -47,9 +70,16 @@ fn setup(ctx: &ReducerContext) {
 fn init(ctx: &ReducerContext) {
     setup(ctx);
 
+    // Module owner (publisher) is the first admin
+    let owner = Player { identity: ctx.sender, is_admin: true, world: String::from(shared::DEFAULT_WORLD) };
+    match ctx.db.player().identity().find(ctx.sender).is_none() {
+        true => ctx.db.player().insert(owner),
+        false => ctx.db.player().identity().update(owner)
+    };
+
     let _ = ctx.db.ticker().try_insert(Ticker {
         scheduled_id: 0,
-        scheduled_at: ScheduleAt::Interval(TimeDuration::from_micros(TICK))
+        scheduled_at: ScheduleAt::Interval(world::tick_interval(world::get(ctx, shared::DEFAULT_WORLD).tps))
     });
 }
 
@@ -69,6 +99,153 @@ fn tick(ctx: &ReducerContext, _arg: Ticker) {
     }
 
     shared::tick_scripts().expect("Tick error");
+
+    // Persist scripts storage
+    store::flush(ctx);
+
+    // Persist changed and evicted chunks and load requested ones
+    chunks::flush(ctx);
+
+    // Metrics once per second of the default world
+    let tps = shared::world::world_config_raw().tps as u64;
+    if shared::timers::current_tick().is_multiple_of(tps.max(1)) {
+        metrics::flush(ctx);
+    }
+}
+
+#[spacetimedb::reducer(client_connected)]
+fn connect(ctx: &ReducerContext) {
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    let player = match get_player(ctx) {
+        Some(player) => player,
+        None => {
+            let world = String::from(shared::DEFAULT_WORLD);
+            ctx.db.player().insert(Player { identity: ctx.sender, is_admin: false, world })
+        }
+    };
+
+    // Scripts of the player's world are notified
+    let event = shared::events::Event::PlayerJoined(ctx.sender.to_string());
+    shared::in_world(&player.world, || shared::events::emit(event));
+}
+
+#[spacetimedb::reducer(client_disconnected)]
+fn disconnect(ctx: &ReducerContext) {
+    if !shared::is_initalized() { return; }
+    let Some(player) = get_player(ctx) else { return };
+
+    // Player's chunks are not loaded anymore
+    shared::in_world(&player.world, || shared::streaming::remove_centre(&ctx.sender.to_string()));
+}
+
+#[spacetimedb::reducer]
+/// Move player's chunks loading centre (world position)
+fn move_player(ctx: &ReducerContext, x: f32, y: f32, z: f32) {
+    let Some(player) = get_player(ctx) else { return };
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    let pos = shared::math::Vec3::new(x, y, z);
+    shared::in_world(&player.world, || shared::streaming::set_centre_world(&ctx.sender.to_string(), pos));
+}
+
+#[spacetimedb::reducer]
+/// Move player into another existing world, loading centre is set by next move
+fn change_world(ctx: &ReducerContext, world: String) -> Result<(), String> {
+    let Some(player) = get_player(ctx) else {
+        return Err(String::from("Player not found"));
+    };
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    if shared::get_world(&world).is_none() {
+        return Err(format!("World {} not found", world));
+    }
+
+    // Chunks of the previous world are not loaded for the player anymore
+    shared::in_world(&player.world, || shared::streaming::remove_centre(&ctx.sender.to_string()));
+    ctx.db.player().identity().update(Player { world, ..player });
+
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Grant or revoke admin rights of the player (admin only), player is created if not exists
+fn set_admin(ctx: &ReducerContext, identity: Identity, is_admin: bool) -> Result<(), String> {
+    require_admin(ctx)?;
+
+    match ctx.db.player().identity().find(identity) {
+        Some(player) => ctx.db.player().identity().update(Player { is_admin, ..player }),
+        None => {
+            let world = String::from(shared::DEFAULT_WORLD);
+            ctx.db.player().insert(Player { identity, is_admin, world })
+        }
+    };
+
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Change or create world config (admin only), applied without republishing
+fn set_world_config(ctx: &ReducerContext, config: world::WorldConfigRow) -> Result<(), String> {
+    require_admin(ctx)?;
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    let world = config.world.clone();
+    world::update(ctx, &world, config.to_config())
+}
+
+#[spacetimedb::reducer]
+/// Enable deterministic scripts scheduling with the seed or disable it (admin only)
+fn set_deterministic(ctx: &ReducerContext, seed: Option<u64>) -> Result<(), String> {
+    require_admin(ctx)?;
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    shared::set_deterministic(seed);
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Export world into region files table (admin only)
+fn export_world(ctx: &ReducerContext, world: String) -> Result<(), String> {
+    require_admin(ctx)?;
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    let count = regions::export(ctx, &world)?;
+    log::info!("World {} exported into {} region files", world, count);
+
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+/// Import region file by it's name into the world (admin only)
+fn import_region(ctx: &ReducerContext, world: String, name: String, data: Vec<u8>) -> Result<(), String> {
+    require_admin(ctx)?;
+
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    let count = regions::import(ctx, &world, &name, &data)?;
+    log::info!("Region {} imported into {}: {} chunks", name, world, count);
+
+    Ok(())
 }
 
 #[spacetimedb::reducer]
@@ -76,7 +253,7 @@ fn tick(ctx: &ReducerContext, _arg: Ticker) {
 fn edit_asset(ctx: &ReducerContext, path: String, value: Vec<u8>) {
     // If player is exists and have admin rights
     let _is_admin = get_player(ctx)
-        .and_then(|p| Some(p.is_admin))
+        .map(|p| p.is_admin)
         .unwrap_or(false);
 
     //Todo: If local-host (always admin access):
@@ -84,7 +261,20 @@ fn edit_asset(ctx: &ReducerContext, path: String, value: Vec<u8>) {
 
     // FIXME: private assets access 
     assets::add_raw_asset(ctx, path, value);
-    return;
+}
+
+#[spacetimedb::reducer]
+/// Call command script
+fn command(ctx: &ReducerContext, name: String, args: Vec<String>) {
+    if !shared::is_initalized() {
+        setup(ctx);
+    }
+
+    match shared::run_command(&name, args) {
+        Ok(true) => (),
+        Ok(false) => log::warn!("Command not found: {}", name),
+        Err(e) => log::error!("Command {} error: {}", name, e)
+    }
 }
 
 #[spacetimedb::reducer]
@@ -92,7 +282,7 @@ fn edit_asset(ctx: &ReducerContext, path: String, value: Vec<u8>) {
 fn remove_asset(ctx: &ReducerContext, path: String) {
     // If player is exists and have admin rights
     let _is_admin = get_player(ctx)
-        .and_then(|p| Some(p.is_admin))
+        .map(|p| p.is_admin)
         .unwrap_or(false);
 
     //Todo: If local-host (always admin access):
@@ -100,5 +290,4 @@ fn remove_asset(ctx: &ReducerContext, path: String) {
 
     // FIXME: private assets access 
     assets::remove_raw_asset(ctx, path);
-    return;
 }
@@ -0,0 +1,108 @@
+use spacetimedb::{ReducerContext, Table};
+
+/// Wall times (microseconds) are client-only: wasm module can't read a clock
+/// inside of a reducer, so the module writes them as zero
+#[spacetimedb::table(name=script_metrics, public)]
+pub struct ScriptMetrics {
+    #[primary_key]
+    path: String,
+
+    invocations: u64,
+    errors: u64,
+
+    min: u64,
+    avg: u64,
+    max: u64,
+    p99: u64,
+
+    live_tasks: u32,
+    threading: u32,
+}
+
+#[spacetimedb::table(name=queue_metrics, public)]
+pub struct QueueMetrics {
+    #[primary_key]
+    /// Queue name
+    name: String,
+    depth: u32,
+}
+
+#[spacetimedb::table(name=chunk_metrics, public)]
+pub struct ChunkMetrics {
+    #[primary_key]
+    /// Lifecycle state name
+    state: String,
+    count: u32,
+}
+
+#[spacetimedb::table(name=memory_metrics, public)]
+pub struct MemoryMetrics {
+    #[primary_key]
+    name: String,
+    value: u64,
+}
+
+fn write_memory(ctx: &ReducerContext, name: &str, value: usize) {
+    let row = MemoryMetrics { name: name.to_string(), value: value as u64 };
+
+    match ctx.db.memory_metrics().name().find(&row.name).is_none() {
+        true => ctx.db.memory_metrics().insert(row),
+        false => ctx.db.memory_metrics().name().update(row)
+    };
+}
+
+fn write_queue(ctx: &ReducerContext, name: &str, depth: usize) {
+    let row = QueueMetrics { name: name.to_string(), depth: depth as u32 };
+
+    match ctx.db.queue_metrics().name().find(&row.name).is_none() {
+        true => ctx.db.queue_metrics().insert(row),
+        false => ctx.db.queue_metrics().name().update(row)
+    };
+}
+
+/// Write Core metrics snapshot into DB
+pub fn flush(ctx: &ReducerContext) {
+    // Replace previous snapshot (removed scripts too)
+    for row in ctx.db.script_metrics().iter() {
+        ctx.db.script_metrics().path().delete(&row.path);
+    }
+
+    for stats in shared::metrics::scripts_stats() {
+        ctx.db.script_metrics().insert(ScriptMetrics {
+            path: stats.path,
+            invocations: stats.invocations,
+            errors: stats.errors,
+            min: stats.min,
+            avg: stats.avg,
+            max: stats.max,
+            p99: stats.p99,
+            live_tasks: stats.live_tasks as u32,
+            threading: stats.threading,
+        });
+    }
+
+    let queues = shared::metrics::queue_stats();
+    write_queue(ctx, "gen_queue", queues.gen_queue);
+    write_queue(ctx, "meshes_queue", queues.meshes_queue);
+
+    for (stage, depth) in shared::pipeline::queue_depths() {
+        write_queue(ctx, &format!("stage_{:?}", stage).to_lowercase(), depth);
+    }
+
+    let memory = shared::residency::memory_stats();
+    write_memory(ctx, "chunks", memory.chunks);
+    write_memory(ctx, "meshes", memory.meshes);
+    write_memory(ctx, "chunk_bytes", memory.chunk_bytes);
+    write_memory(ctx, "mesh_bytes", memory.mesh_bytes);
+    write_memory(ctx, "persisted", memory.persisted);
+    write_memory(ctx, "budget", memory.budget);
+
+    for (state, count) in shared::lifecycle::state_counts() {
+        let row = ChunkMetrics { state: state.name().to_string(), count: count as u32 };
+
+        match ctx.db.chunk_metrics().state().find(&row.state).is_none() {
+            true => ctx.db.chunk_metrics().insert(row),
+            false => ctx.db.chunk_metrics().state().update(row)
+        };
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::*;
+use spacetimedb::{ReducerContext, Table};
+use shared::residency::StoredChunk;
+
+use crate::chunk;
+
+/// Exported region files, written to disk by syncer
+#[spacetimedb::table(name=region_export, public)]
+pub struct RegionExport {
+    #[primary_key]
+    /// World and file name: `world/r.x.y.z.region`
+    pub path: String,
+
+    #[index(btree)]
+    pub world: String,
+    pub data: Vec<u8>,
+}
+
+/// Export stored and resident chunks of the world, returns files count
+pub fn export(ctx: &ReducerContext, world: &str) -> Result<usize, String> {
+    if shared::get_world(world).is_none() {
+        return Err(format!("World {} not found", world));
+    }
+
+    // Resident chunks are newer than stored ones
+    let mut chunks = BTreeMap::new();
+    for row in ctx.db.chunk().world().filter(world) {
+        let pos = shared::math::IVec3::new(row.px, row.py, row.pz);
+        let stored = StoredChunk { world: row.world, pos, data: row.data, biomes: row.biomes, stage: row.stage };
+
+        chunks.insert(pos.to_array(), stored);
+    }
+
+    shared::in_world(world, || {
+        for pos in shared::chunk_positions() {
+            let Some(stored) = shared::snapshot_chunk(pos) else { continue };
+            chunks.insert(pos.to_array(), stored);
+        }
+    });
+
+    let files = export_files(chunks.into_values())?;
+
+    // Previous export of the world is replaced
+    let old = ctx.db.region_export().world().filter(world).map(|row| row.path).collect::<Vec<_>>();
+    for path in old {
+        ctx.db.region_export().path().delete(&path);
+    }
+
+    let count = files.len();
+    for row in files {
+        ctx.db.region_export().insert(row);
+    }
+
+    Ok(count)
+}
+
+/// Encode chunks into region files rows
+fn export_files(chunks: impl IntoIterator<Item = StoredChunk>) -> Result<Vec<RegionExport>, String> {
+    let files = shared::region_file::export(chunks)?;
+
+    Ok(files.into_iter().map(|(world, region, data)| {
+        let path = format!("{}/{}", world, shared::region_file::file_name(region));
+        RegionExport { path, world, data }
+    }).collect())
+}
+
+/// Decode region file by it's name into the world chunks
+fn import_file(world: &str, name: &str, data: &[u8]) -> Result<Vec<StoredChunk>, String> {
+    let region = shared::region_file::parse_file_name(name)
+        .ok_or_else(|| format!("Invalid region file name {}", name))?;
+
+    shared::region_file::decode(world, region, data)
+}
+
+/// Import region file into the world, returns chunks count
+pub fn import(ctx: &ReducerContext, world: &str, name: &str, data: &[u8]) -> Result<usize, String> {
+    let chunks = import_file(world, name, data)?;
+    let positions = chunks.iter().map(|c| c.pos).collect::<Vec<_>>();
+
+    for stored in chunks {
+        crate::chunks::store(ctx, stored);
+    }
+
+    // Imported chunks replace resident ones, they are loaded from storage
+    shared::residency::add_persisted(world, positions.iter().copied());
+    shared::in_world(world, || {
+        for pos in &positions {
+            shared::residency::discard(*pos);
+        }
+    });
+
+    Ok(positions.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::math::IVec3;
+    use super::*;
+
+    #[test]
+    fn export_import_round_trip() {
+        let chunks = [IVec3::new(0, 0, 0), IVec3::new(40, -3, 7), IVec3::new(-1, 2, -33)].map(|pos| StoredChunk {
+            world: String::from("overworld"),
+            pos,
+            data: (0..shared::chunk::BUF_SIZE).map(|i| (i as i32 ^ pos.z) as u8).collect(),
+            biomes: vec![pos.x as u16; shared::chunk::SIZE * shared::chunk::SIZE],
+            stage: 3,
+        });
+
+        let files = export_files(chunks.clone()).expect("Export error");
+        assert_eq!(files.len(), 3);
+
+        // Exported files are imported by file name into another world
+        let mut imported = Vec::new();
+        for row in &files {
+            let (world, name) = row.path.split_once('/').expect("Path without world");
+            assert_eq!((world, row.world.as_str()), ("overworld", "overworld"));
+
+            imported.extend(import_file("nether", name, &row.data).expect("Import error"));
+        }
+        imported.sort_by_key(|c| c.pos.to_array());
+
+        let mut expected = chunks.to_vec();
+        expected.sort_by_key(|c| c.pos.to_array());
+
+        assert_eq!(imported.len(), expected.len());
+        for (a, b) in imported.iter().zip(&expected) {
+            assert_eq!((a.world.as_str(), a.pos, &a.data, &a.biomes, a.stage), ("nether", b.pos, &b.data, &b.biomes, b.stage));
+        }
+
+        assert!(import_file("nether", "region.bin", &files[0].data).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use spacetimedb::{ReducerContext, Table};
+use shared::store::StoreChange;
+
+#[spacetimedb::table(name=script_store)]
+pub struct StoreEntry {
+    #[primary_key]
+    /// Script path (length-prefixed) and key formated key
+    key: String,
+
+    #[index(btree)]
+    script: String,
+    name: String,
+
+    /// Serialized (JSON) value
+    value: String
+}
+
+/// Script length prefix keeps keys of different scripts apart (`a:b` + `c` and `a` + `b:c`)
+fn entry_key(script: &str, name: &str) -> String {
+    format!("{}:{}:{}", script.len(), script, name)
+}
+
+/// Load stored values into Core
+pub fn init(ctx: &ReducerContext) {
+    // Entries of the previous key format
+    let old = ctx.db.script_store().iter()
+        .filter(|e| e.key != entry_key(&e.script, &e.name))
+        .collect::<Vec<_>>();
+
+    for entry in old {
+        ctx.db.script_store().key().delete(&entry.key);
+
+        let key = entry_key(&entry.script, &entry.name);
+        if ctx.db.script_store().key().find(&key).is_none() {
+            ctx.db.script_store().insert(StoreEntry { key, ..entry });
+        }
+    }
+
+    shared::store::load_store(
+        ctx.db.script_store().iter().map(|e| (e.script, e.name, e.value))
+    );
+}
+
+/// Write scripts storage changes into DB
+pub fn flush(ctx: &ReducerContext) {
+    for change in shared::store::take_changes() {
+        match change {
+            StoreChange::Set { script, key: name, value } => {
+                let key = entry_key(&script, &name);
+                let entry = StoreEntry { key, script, name, value };
+
+                // Insert or update entry
+                match ctx.db.script_store().key().find(&entry.key).is_none() {
+                    true => ctx.db.script_store().insert(entry),
+                    false => ctx.db.script_store().key().update(entry)
+                };
+            },
+            StoreChange::Remove { script, key: name } => {
+                ctx.db.script_store().key().delete(entry_key(&script, &name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_keys_do_not_collide() {
+        assert_ne!(entry_key("a:b", "c"), entry_key("a", "b:c"));
+        assert_eq!(entry_key("scripts/a.rn", "x"), entry_key("scripts/a.rn", "x"));
+    }
+}
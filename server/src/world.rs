@@ -0,0 +1,130 @@
+use spacetimedb::{ReducerContext, ScheduleAt, Table, TimeDuration};
+use shared::math::IVec3;
+use shared::chunk::RnIVec3;
+use shared::world::WorldConfig;
+
+use crate::{ticker, Ticker, DURATION};
+
+/// Flat generator layer
+#[derive(spacetimedb::SpacetimeType, Debug, Clone)]
+pub struct FlatLayer {
+    pub block: String,
+    pub thickness: u32,
+}
+
+#[spacetimedb::table(name=world_config, public)]
+pub struct WorldConfigRow {
+    #[primary_key]
+    /// World id
+    pub world: String,
+
+    pub seed: u64,
+    /// Generator name: script, flat or noise
+    pub generator: String,
+    pub view_distance: i32,
+    pub tps: u32,
+
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+    pub spawn_z: i32,
+
+    /// World height of the flat generator bottom layer
+    pub flat_base: i32,
+    /// From bottom to top
+    pub flat_layers: Vec<FlatLayer>,
+}
+
+impl WorldConfigRow {
+    pub fn to_config(&self) -> WorldConfig {
+        WorldConfig {
+            seed: self.seed,
+            generator: self.generator.clone(),
+            view_distance: self.view_distance,
+            tps: self.tps,
+            spawn: RnIVec3(IVec3::new(self.spawn_x, self.spawn_y, self.spawn_z)),
+            flat_base: self.flat_base,
+            flat_layers: self.flat_layers.iter().map(|l| (l.block.clone(), l.thickness)).collect(),
+        }
+    }
+
+    fn from_config(world: &str, config: &WorldConfig) -> Self {
+        Self {
+            world: world.to_string(),
+            seed: config.seed,
+            generator: config.generator.clone(),
+            view_distance: config.view_distance,
+            tps: config.tps,
+            spawn_x: config.spawn.0.x,
+            spawn_y: config.spawn.0.y,
+            spawn_z: config.spawn.0.z,
+            flat_base: config.flat_base,
+            flat_layers: config.flat_layers.iter()
+                .map(|(block, thickness)| FlatLayer { block: block.clone(), thickness: *thickness })
+                .collect(),
+        }
+    }
+}
+
+/// World config row (default one is inserted)
+pub fn get(ctx: &ReducerContext, world: &str) -> WorldConfigRow {
+    match ctx.db.world_config().world().find(world.to_string()) {
+        Some(row) => row,
+        None => ctx.db.world_config().insert(WorldConfigRow::from_config(world, &WorldConfig::default()))
+    }
+}
+
+/// Load configs of all worlds into Core, default world always exists
+pub fn init(ctx: &ReducerContext) {
+    get(ctx, shared::DEFAULT_WORLD);
+
+    for row in ctx.db.world_config().iter() {
+        if let Err(e) = shared::in_world(&row.world, || shared::world::apply(row.to_config())) {
+            log::error!("World {} config error: {}", row.world, e);
+        }
+    }
+}
+
+/// Tick interval in micros
+pub fn tick_interval(tps: u32) -> TimeDuration {
+    TimeDuration::from_micros(DURATION / tps.max(1) as i64)
+}
+
+/// Validate, store and apply world config, world is created if not exists
+pub fn update(ctx: &ReducerContext, world: &str, config: WorldConfig) -> Result<(), String> {
+    let tps = config.tps;
+    let row = WorldConfigRow::from_config(world, &config);
+
+    shared::in_world(world, || shared::world::apply(config))?;
+
+    match ctx.db.world_config().world().find(&row.world).is_none() {
+        true => ctx.db.world_config().insert(row),
+        false => ctx.db.world_config().world().update(row)
+    };
+
+    // Default world TPS drives the ticker
+    if world != shared::DEFAULT_WORLD { return Ok(()); }
+
+    // Reschedule ticker with new TPS
+    for ticker in ctx.db.ticker().iter() {
+        ctx.db.ticker().scheduled_id().update(Ticker {
+            scheduled_at: ScheduleAt::Interval(tick_interval(tps)),
+            ..ticker
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_layers_round_trip() {
+        let config = WorldConfig { flat_base: 10, flat_layers: vec![(String::from("Stone"), 5)], ..Default::default() };
+        let row = WorldConfigRow::from_config("flat", &config);
+
+        assert_eq!(row.to_config().flat_layers, config.flat_layers);
+        assert_eq!(row.to_config().flat_base, 10);
+    }
+}
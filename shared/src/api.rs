@@ -0,0 +1,211 @@
+//! Morph module API reference: machine-readable metadata (JSON) and Markdown docs.
+//! Output can be used by editors for `.rn` files completion.
+//!
+//! Rune can't list items of an installed context, so Morph items are recorded while
+//! the module is built, and enum constructors are checked in the installed context.
+
+use std::{fmt::Write, path::*};
+use rune::{compile::Named, module::InstallWith, runtime::{MaybeTypeOf, Protocol, TypeOf, UnsafeToRef}};
+
+/// Metadata generated by `#[rune::function]`, the same that is installed into the module
+pub type FunctionMeta = fn() -> rune::alloc::Result<rune::__private::FunctionMetaData>;
+
+/// Enum with Rune constructors, variants are comparable in scripts
+pub trait ApiEnum: Named + TypeOf + MaybeTypeOf + UnsafeToRef + InstallWith + std::fmt::Debug + Eq + Sized + 'static {
+    /// All variants, only ones with installed constructors are documented
+    const VARIANTS: &'static [Self];
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiFunction {
+    /// Full item path, `Type::name` for instance functions
+    pub path: String,
+    pub args: Vec<String>,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiType {
+    pub path: String,
+    /// Enum constructors
+    pub variants: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Api {
+    pub constants: Vec<(String, usize)>,
+    pub types: Vec<ApiType>,
+    pub functions: Vec<ApiFunction>,
+}
+
+/// Rune module that records documented Morph items
+pub struct ApiModule {
+    module: rune::Module,
+    api: Api,
+
+    /// Type index and it's variants with constructors hashes
+    variants: Vec<(usize, Vec<(String, rune::Hash)>)>,
+}
+
+impl Default for ApiModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiModule {
+    pub fn new() -> Self {
+        Self { module: rune::Module::new(), api: Api::default(), variants: Vec::new() }
+    }
+
+    /// Module for not documented items: fields, protocols and macros
+    pub fn module(&mut self) -> &mut rune::Module {
+        &mut self.module
+    }
+
+    pub fn constant(&mut self, name: &str, value: usize) -> rune::support::Result<()> {
+        self.module.constant(name, value).build()?;
+        self.api.constants.push((name.to_string(), value));
+
+        Ok(())
+    }
+
+    pub fn ty<T: Named + TypeOf + InstallWith>(&mut self) -> rune::support::Result<()> {
+        self.module.ty::<T>()?;
+        self.api.types.push(ApiType { path: T::ITEM.to_string(), variants: Vec::new() });
+
+        Ok(())
+    }
+
+    pub fn enum_ty<T: ApiEnum>(&mut self) -> rune::support::Result<()> {
+        self.ty::<T>()?;
+        self.module.associated_function(&Protocol::PARTIAL_EQ, |a: &T, b: &T| a == b)?;
+        self.module.associated_function(&Protocol::EQ, |a: &T, b: &T| a == b)?;
+
+        let mut variants = Vec::with_capacity(T::VARIANTS.len());
+        for variant in T::VARIANTS {
+            let name = format!("{:?}", variant);
+            let hash = rune::Hash::type_hash(&T::ITEM.join([name.as_str()])?);
+
+            variants.push((name, hash));
+        }
+
+        self.variants.push((self.api.types.len() - 1, variants));
+        Ok(())
+    }
+
+    pub fn function(&mut self, meta: FunctionMeta) -> rune::support::Result<()> {
+        self.record(None, meta)?;
+        self.module.function_meta(meta)?;
+
+        Ok(())
+    }
+
+    /// Instance function of the type
+    pub fn method<T: Named>(&mut self, meta: FunctionMeta) -> rune::support::Result<()> {
+        self.record(Some(T::ITEM), meta)?;
+        self.module.function_meta(meta)?;
+
+        Ok(())
+    }
+
+    fn record(&mut self, ty: Option<&rune::Item>, meta: FunctionMeta) -> rune::support::Result<()> {
+        let statics = meta()?.statics;
+
+        let path = match ty {
+            Some(ty) => format!("{}::{}", ty, statics.name),
+            None => statics.name.to_string()
+        };
+        let args = statics.arguments.iter().map(|a| a.to_string()).collect();
+        let docs = statics.docs.iter().map(|l| l.trim().to_string()).collect();
+
+        self.api.functions.push(ApiFunction { path, args, docs });
+        Ok(())
+    }
+
+    /// Install module into the context, returns it's API
+    pub fn install(self, context: &mut rune::Context) -> rune::support::Result<Api> {
+        let Self { module, mut api, variants } = self;
+        context.install(module)?;
+
+        // Variants without constructor can't be created by scripts
+        let runtime = context.runtime()?;
+        for (index, variants) in variants {
+            api.types[index].variants = variants.into_iter()
+                .filter(|(_, hash)| runtime.function(hash).is_some())
+                .map(|(name, _)| name)
+                .collect();
+        }
+
+        api.types.sort_by(|a, b| a.path.cmp(&b.path));
+        api.functions.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(api)
+    }
+}
+
+impl Api {
+    /// Build Morph module context and collect it's API
+    pub fn new() -> rune::support::Result<Self> {
+        let mut context = rune::Context::with_default_modules()?;
+        crate::morph_module()?.install(&mut context)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "constants": self.constants.iter()
+                .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>(),
+            "types": self.types.iter()
+                .map(|t| serde_json::json!({ "path": t.path, "variants": t.variants }))
+                .collect::<Vec<_>>(),
+            "functions": self.functions.iter()
+                .map(|f| serde_json::json!({ "path": f.path, "args": f.args, "docs": f.docs }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::from("# Morph module API\n");
+
+        // Writing into String never fails
+        let _ = writeln!(result, "\n## Constants\n");
+        for (name, value) in &self.constants {
+            let _ = writeln!(result, "- `{}` = `{}`", name, value);
+        }
+
+        let _ = writeln!(result, "\n## Types\n");
+        for ty in &self.types {
+            let _ = writeln!(result, "### `{}`\n", ty.path);
+            for variant in &ty.variants {
+                let _ = writeln!(result, "- `{}::{}`", ty.path, variant);
+            }
+
+            if !ty.variants.is_empty() { result.push('\n'); }
+        }
+
+        let _ = writeln!(result, "## Functions\n");
+        for function in &self.functions {
+            let _ = writeln!(result, "### `{}({})`\n", function.path, function.args.join(", "));
+            for line in &function.docs {
+                let _ = writeln!(result, "{}", line);
+            }
+
+            if !function.docs.is_empty() { result.push('\n'); }
+        }
+
+        result
+    }
+}
+
+/// Write `morph.json` and `morph.md` into the directory
+pub fn generate(dir: impl AsRef<Path>) -> rune::support::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let api = Api::new()?;
+    std::fs::write(dir.join("morph.json"), serde_json::to_string_pretty(&api.to_json())?)?;
+    std::fs::write(dir.join("morph.md"), api.to_markdown())?;
+
+    Ok(())
+}
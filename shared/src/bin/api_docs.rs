@@ -0,0 +1,12 @@
+//! Generate Morph module API reference: `cargo run -p shared --bin api_docs -- docs/api`
+
+fn main() {
+    let dir = std::env::args().nth(1).unwrap_or(String::from("docs/api"));
+
+    if let Err(e) = shared::api::generate(&dir) {
+        eprintln!("API generation error: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("API reference written to {}", dir);
+}
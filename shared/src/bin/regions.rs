@@ -0,0 +1,27 @@
+//! Inspect exported region files: `cargo run -p shared --bin regions -- regions`
+
+use std::collections::*;
+
+fn main() {
+    let dir = std::env::args().nth(1).unwrap_or(String::from("regions"));
+
+    let chunks = match shared::region_file::read_dir(&dir) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            eprintln!("Regions read error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // World -> regions and chunks count
+    let mut worlds: BTreeMap<String, (BTreeSet<[i32; 3]>, usize)> = BTreeMap::new();
+    for chunk in &chunks {
+        let (regions, count) = worlds.entry(chunk.world.clone()).or_default();
+        regions.insert(shared::region_file::region_pos(chunk.pos).to_array());
+        *count += 1;
+    }
+
+    for (world, (regions, count)) in &worlds {
+        println!("{}: {} regions, {} chunks", world, regions.len(), count);
+    }
+}
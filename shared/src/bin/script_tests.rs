@@ -0,0 +1,16 @@
+//! Run Rune tests of the scripts directory: `cargo run -p shared --bin script_tests -- assets/scripts`
+
+fn main() {
+    let dir = std::env::args().nth(1).unwrap_or(String::from("assets/scripts"));
+
+    match shared::testing::run_dir(&dir) {
+        Ok(report) => {
+            println!("{}", report);
+            if !report.is_success() { std::process::exit(1); }
+        },
+        Err(e) => {
+            eprintln!("Scripts tests error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
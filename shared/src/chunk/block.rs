@@ -16,7 +16,13 @@ pub enum Direction {
     Forward,
 }
 
-#[derive(Debug, Clone, Copy, rune::Any)]
+impl crate::api::ApiEnum for Direction {
+    const VARIANTS: &'static [Self] = &[
+        Self::Left, Self::Right, Self::Down, Self::Up, Self::Back, Self::Forward,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, rune::Any)]
 pub enum ModelType {
     #[rune(constructor)]
     Full,
@@ -27,6 +33,10 @@ pub enum ModelType {
     //Custom(#[rune(get)] u32)
 }
 
+impl crate::api::ApiEnum for ModelType {
+    const VARIANTS: &'static [Self] = &[Self::Full, Self::Slab, Self::Stair];
+}
+
 #[derive(Debug, rune::Any, Clone)]
 pub struct Model {
     model: ModelType,
@@ -53,6 +63,10 @@ pub(crate) fn init_blocks() {
 #[derive(Debug, rune::Any, Clone)]
 pub struct BlockType {
     pub model: Option<Model>,
+
+    /// Can be removed by world generation (caves), true by default
+    #[rune(get, set)]
+    pub replaceable: bool,
     // todo: light, collisions etc
 }
 
@@ -79,7 +93,7 @@ pub fn add_block(name: String, model: Option<Model>) {
     let mut names = handler.names.write().unwrap();
     if let Some(id) = names.get(&name) { 
         let mut blocks = handler.blocks.write().unwrap();
-        blocks[*id as usize] = BlockType { model };
+        blocks[*id as usize] = BlockType { model, replaceable: true };
         
         return;
     }
@@ -88,7 +102,25 @@ pub fn add_block(name: String, model: Option<Model>) {
     names.insert(name, id);
 
     let mut blocks = handler.blocks.write().unwrap();
-    blocks.push(BlockType { model });
+    blocks.push(BlockType { model, replaceable: true });
+}
+
+#[rune::function]
+/// Set can block be removed by world generation
+pub fn set_replaceable(name: String, value: bool) {
+    let handler = VALUE.get().unwrap();
+    let Some(id) = handler.names.read().unwrap().get(&name).copied() else { return };
+
+    let mut blocks = handler.blocks.write().unwrap();
+    blocks[id as usize].replaceable = value;
+}
+
+/// Replaceable flags of all blocks by id
+pub fn replaceable_mask() -> Vec<bool> {
+    let handler = VALUE.get().unwrap();
+    let guard = handler.blocks.read().unwrap();
+
+    guard.iter().map(|b| b.replaceable).collect()
 }
 
 /// Get block data by type
@@ -100,6 +132,14 @@ pub fn block_type(id: u32) -> Option<BlockType> {
     guard.get(id as usize).cloned()
 }
 
+/// Find block index by name
+pub fn find_block(name: &str) -> Option<u16> {
+    let handler = VALUE.get().unwrap();
+    let guard = handler.names.read().unwrap();
+
+    guard.get(name).map(|id| *id as u16)
+}
+
 #[rune::function]
 /// Return block index by name OR default index (0)
 pub fn block_id(name: String) -> u32 {
@@ -111,5 +151,5 @@ pub fn block_id(name: String) -> u32 {
 
 #[rune::function]
 pub fn model_type(block: &BlockType) -> Option<ModelType> {
-    block.model.as_ref().and_then(|m| Some(m.model))
+    block.model.as_ref().map(|m| m.model)
 }
@@ -1,7 +1,9 @@
 mod block;
+mod region;
 mod utils;
 
 pub use block::*;
+pub use region::*;
 pub use utils::*;
 use std::sync::*;
 
@@ -28,9 +30,19 @@ impl RawChunk {
         Self(std::iter::repeat_n(0, BUF_SIZE).collect())
     }
 
+    /// Create chunk from raw buffer
+    pub fn from_bytes(value: Vec<u8>) -> Option<Self> {
+        (value.len() == BUF_SIZE).then_some(Self(value))
+    }
+
+    /// Raw chunk buffer
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
     pub fn get_block(&self, index: usize) -> u16 {
         let i = index * BLOCK_SIZE / BYTE;
-        let (a, b) = match index % 2 == 0 {
+        let (a, b) = match index.is_multiple_of(2) {
             // First and second bytes
             // 0110_0001 1001_0010 1110_1000 => 0110_0001 and 1001
             true => (self.0[i] as u16, (self.0[i + 1] >> HALF_BYTE) as u16),
@@ -44,7 +56,7 @@ impl RawChunk {
 
     pub fn set_block(&mut self, index: usize, value: u16) {
         let i = index * BLOCK_SIZE / BYTE;
-        if index % 2 == 0 {
+        if index.is_multiple_of(2) {
             // 0000_0101_1100_0011 => 0101_1100 and 0011
             let (a, b) = ((value >> HALF_BYTE) as u8, (value & 0b1111) as u8);
 
@@ -66,10 +78,21 @@ impl RawChunk {
 
         (x + y + z) as usize
     }
+
+    /// Local block position from index
+    pub fn block_position(index: usize) -> IVec3 {
+        let index = index as i32;
+        IVec3::new(index % SIZE_I32, index / SIZE_I32.pow(2), index / SIZE_I32 % SIZE_I32)
+    }
 }
 
 #[derive(Debug, rune::Any, Clone)]
-pub struct Chunk(Arc<RwLock<RawChunk>>);
+pub struct Chunk {
+    raw: Arc<RwLock<RawChunk>>,
+
+    /// World and position while chunk is in the world
+    location: Arc<RwLock<Option<(String, IVec3)>>>,
+}
 
 impl Chunk {
     pub fn empty() -> Self {
@@ -77,15 +100,43 @@ impl Chunk {
     }
 
     pub fn new(raw: RawChunk) -> Self {
-        Self(Arc::new(RwLock::new(raw)))
+        Self { raw: Arc::new(RwLock::new(raw)), location: Default::default() }
     }
 
     pub fn read(&self) -> RwLockReadGuard<'_, RawChunk> {
-        self.0.read().unwrap()
+        self.raw.read().unwrap()
     }
 
     pub fn write(&self) -> RwLockWriteGuard<'_, RawChunk> {
-        self.0.write().unwrap()
+        self.raw.write().unwrap()
+    }
+
+    /// Is it the same chunk data
+    pub fn same(&self, other: &Chunk) -> bool {
+        Arc::ptr_eq(&self.raw, &other.raw)
+    }
+
+    /// Mark chunk as inserted into the world
+    pub(crate) fn place(&self, world: &str, pos: IVec3) {
+        *self.location.write().unwrap() = Some((world.to_string(), pos));
+    }
+
+    /// Mark chunk as removed from the world
+    pub(crate) fn detach(&self) {
+        *self.location.write().unwrap() = None;
+    }
+
+    /// Emit `block_changed` for the local box (inclusive) if chunk is in the world,
+    /// changed chunk is stored again
+    pub(crate) fn changed(&self, min: IVec3, max: IVec3) {
+        let Some((world, pos)) = self.location.read().unwrap().clone() else { return };
+
+        if let Some(core) = crate::get_world(&world) {
+            crate::residency::mark_dirty(&core, pos);
+        }
+
+        let origin = pos * SIZE_I32;
+        crate::events::emit_in(world, crate::events::Event::BlockChanged(origin + min, origin + max));
     }
 }
 
@@ -163,23 +214,26 @@ pub fn get_block(chunk: &Chunk, index: usize) -> u16 {
 
 #[rune::function(instance)]
 pub fn set_block(chunk: &Chunk, index: usize, value: u16) {
-    chunk.write().set_block(index, value)
+    chunk.write().set_block(index, value);
+
+    let local = RawChunk::block_position(index);
+    chunk.changed(local, local);
 }
 
 #[rune::function]
 /// Convert block position into index
-pub fn position_index(position: RnIVec3) -> usize {
+pub fn position_index(position: &RnIVec3) -> usize {
     RawChunk::block_index(position.0)
 }
 
 #[rune::function]
 /// Get chunk refs data
-pub fn get_refs(pos: RnIVec3) -> Option<ChunksRefs> {
+pub fn get_refs(pos: &RnIVec3) -> Option<ChunksRefs> {
     ChunksRefs::new(pos.0)
 }
 
 /// Get chunk refs block
 #[rune::function(instance)]
-pub fn refs_block(refs: &ChunksRefs, pos: RnIVec3) -> u16 {
+pub fn refs_block(refs: &ChunksRefs, pos: &RnIVec3) -> u16 {
     refs.get_block(pos.0)
 }
\ No newline at end of file
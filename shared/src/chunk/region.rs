@@ -0,0 +1,214 @@
+use rune::runtime::VmResult;
+use super::*;
+
+/// Max blocks count of copied region
+pub const MAX_REGION_VOLUME: usize = 1 << 24;
+
+/// Blocks count of the box size, error if it's negative, overflows or exceeds the cap
+pub fn checked_volume([x, y, z]: [i64; 3]) -> Result<usize, String> {
+    let volume = match x > 0 && y > 0 && z > 0 {
+        true => x.checked_mul(y).and_then(|v| v.checked_mul(z)),
+        false => None
+    };
+
+    match volume.and_then(|v| usize::try_from(v).ok()) {
+        Some(volume) if volume <= MAX_REGION_VOLUME => Ok(volume),
+        _ => Err(format!("Region size [{}, {}, {}] is invalid or exceeds {} blocks", x, y, z, MAX_REGION_VOLUME))
+    }
+}
+
+/// World blocks box, both corners are inclusive
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl Region {
+    pub fn new(a: IVec3, b: IVec3) -> Self {
+        Self { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.max - self.min + IVec3::ONE
+    }
+
+    /// Blocks count, error if the region is too large
+    pub fn volume(&self) -> Result<usize, String> {
+        let size = |max: i32, min: i32| max as i64 - min as i64 + 1;
+        checked_volume([size(self.max.x, self.min.x), size(self.max.y, self.min.y), size(self.max.z, self.min.z)])
+    }
+
+    /// Run closure for every existing chunk of the region with it's write lock taken once.
+    /// Closure gets chunk buffer, block index and world position
+    pub fn edit(&self, f: impl FnMut(&mut RawChunk, usize, IVec3)) {
+        self.visit(true, f);
+    }
+
+    /// Same as `edit`, but blocks are only read: `block_changed` is not emitted
+    pub fn read(&self, f: impl FnMut(&mut RawChunk, usize, IVec3)) {
+        self.visit(false, f);
+    }
+
+    fn visit(&self, changed: bool, mut f: impl FnMut(&mut RawChunk, usize, IVec3)) {
+        let size = IVec3::splat(SIZE_I32);
+        let (from, to) = (self.min.div_euclid(size), self.max.div_euclid(size));
+
+        for cy in from.y..=to.y {
+            for cz in from.z..=to.z {
+                for cx in from.x..=to.x {
+                    let pos = IVec3::new(cx, cy, cz);
+                    let Some(chunk) = crate::_get_chunk(pos) else { continue };
+
+                    // Region part inside of the chunk (local)
+                    let origin = pos * SIZE_I32;
+                    let min = (self.min - origin).max(IVec3::ZERO);
+                    let max = (self.max - origin).min(IVec3::splat(SIZE_I32 - 1));
+
+                    let mut raw = chunk.write();
+                    for y in min.y..=max.y {
+                        for z in min.z..=max.z {
+                            for x in min.x..=max.x {
+                                let local = IVec3::new(x, y, z);
+                                f(&mut raw, RawChunk::block_index(local), origin + local);
+                            }
+                        }
+                    }
+                    drop(raw);
+
+                    // One event per chunk
+                    if changed {
+                        chunk.changed(min, max);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Copied blocks of the region
+#[derive(rune::Any, Debug, Clone)]
+pub struct Blocks {
+    pub size: IVec3,
+    /// Index: x + z * size.x + y * size.x * size.z
+    pub data: Vec<u16>,
+}
+
+impl Blocks {
+    /// Check size and data length, returns blocks count
+    pub fn volume(&self) -> Result<usize, String> {
+        let volume = checked_volume(self.size.to_array().map(i64::from))?;
+
+        match volume == self.data.len() {
+            true => Ok(volume),
+            false => Err(format!("Blocks size {} doesn't match {} blocks", self.size, self.data.len()))
+        }
+    }
+
+    pub fn index(&self, pos: IVec3) -> usize {
+        (pos.x + pos.z * self.size.x + pos.y * self.size.x * self.size.z) as usize
+    }
+}
+
+// ----------------------------------------------------------------------------------------------
+// Region functions
+
+#[rune::function]
+/// Create region from two corners
+pub fn region(a: &RnIVec3, b: &RnIVec3) -> Region {
+    Region::new(a.0, b.0)
+}
+
+#[rune::function]
+/// Fill box of world blocks, returns count of changed blocks
+pub fn fill_box(min: &RnIVec3, max: &RnIVec3, id: u16) -> usize {
+    let mut count = 0;
+    Region::new(min.0, max.0).edit(|raw, index, _| {
+        raw.set_block(index, id);
+        count += 1;
+    });
+
+    count
+}
+
+#[rune::function]
+/// Fill chunk's layer with block
+pub fn fill_layer(chunk: &Chunk, y: i32, id: u16) {
+    if !(0..SIZE_I32).contains(&y) { return; }
+
+    let mut raw = chunk.write();
+    for z in 0..SIZE_I32 {
+        for x in 0..SIZE_I32 {
+            raw.set_block(RawChunk::block_index(IVec3::new(x, y, z)), id);
+        }
+    }
+    drop(raw);
+
+    chunk.changed(IVec3::new(0, y, 0), IVec3::new(SIZE_I32 - 1, y, SIZE_I32 - 1));
+}
+
+#[rune::function]
+/// Replace blocks in the region, returns count of replaced blocks
+pub fn replace(region: &Region, from: u16, to: u16) -> usize {
+    let mut count = 0;
+    region.edit(|raw, index, _| {
+        if raw.get_block(index) != from { return; }
+
+        raw.set_block(index, to);
+        count += 1;
+    });
+
+    count
+}
+
+#[rune::function]
+/// Copy blocks of the region (missing chunks are copied as 0)
+pub fn copy_region(region: &Region) -> VmResult<Blocks> {
+    let volume = match region.volume() {
+        Ok(volume) => volume,
+        Err(message) => return VmResult::panic(message)
+    };
+    let mut blocks = Blocks { size: region.size(), data: vec![0; volume] };
+
+    region.read(|raw, index, world| {
+        let i = blocks.index(world - region.min);
+        blocks.data[i] = raw.get_block(index);
+    });
+
+    VmResult::Ok(blocks)
+}
+
+#[rune::function]
+/// Paste copied blocks with minimal corner at position
+pub fn paste_region(blocks: &Blocks, pos: &RnIVec3) -> VmResult<()> {
+    if let Err(message) = blocks.volume() {
+        return VmResult::panic(message);
+    }
+
+    let Some(max) = pos.0.checked_add(blocks.size - IVec3::ONE) else {
+        return VmResult::panic(format!("Blocks of size {} at {} are out of the world", blocks.size, pos.0));
+    };
+
+    Region::new(pos.0, max).edit(|raw, index, world| {
+        raw.set_block(index, blocks.data[blocks.index(world - pos.0)]);
+    });
+
+    VmResult::Ok(())
+}
+
+#[rune::function]
+/// Set all chunk blocks from array of SIZE_P3 ids
+pub fn set_blocks(chunk: &Chunk, blocks: Vec<u16>) -> VmResult<()> {
+    if blocks.len() != SIZE_P3 {
+        return VmResult::panic(format!("Expected {} blocks, got {}", SIZE_P3, blocks.len()));
+    }
+
+    let mut raw = chunk.write();
+    for (index, id) in blocks.into_iter().enumerate() {
+        raw.set_block(index, id);
+    }
+    drop(raw);
+
+    chunk.changed(IVec3::ZERO, IVec3::splat(SIZE_I32 - 1));
+    VmResult::Ok(())
+}
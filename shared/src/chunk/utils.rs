@@ -1,7 +1,10 @@
 pub(super) use crate::math::*;
+use rune::runtime::{Hasher, Protocol, VmResult};
+use std::hash::Hash;
+use super::SIZE_I32;
 
 /// Bevy IVec3's representation in Rune
-#[derive(rune::Any, Clone, Copy)]
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RnIVec3(pub IVec3);
 
 impl RnIVec3 {
@@ -10,7 +13,211 @@ impl RnIVec3 {
     }
 }
 
+/// Bevy Vec3's representation in Rune
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq)]
+pub struct RnVec3(pub Vec3);
+
+impl RnVec3 {
+    pub fn new(value: Vec3) -> Self {
+        Self(value)
+    }
+}
+
 #[rune::function]
 pub fn ivec3(x: i32, y: i32, z: i32) -> RnIVec3 {
     RnIVec3(IVec3::new(x, y, z))
 }
+
+#[rune::function]
+pub fn vec3(x: f64, y: f64, z: f64) -> RnVec3 {
+    RnVec3(Vec3::new(x as f32, y as f32, z as f32))
+}
+
+// ----------------------------------------------------------------------------------------------
+// IVec3 functions
+
+/// Component-wise checked operation, VM error instead of overflow
+fn checked(a: IVec3, b: IVec3, op: fn(i32, i32) -> Option<i32>, name: &str) -> VmResult<RnIVec3> {
+    match (op(a.x, b.x), op(a.y, b.y), op(a.z, b.z)) {
+        (Some(x), Some(y), Some(z)) => VmResult::Ok(RnIVec3(IVec3::new(x, y, z))),
+        _ => VmResult::panic(format!("{} of {} and {} is out of range", name, a, b))
+    }
+}
+
+#[rune::function(instance, protocol = ADD)]
+fn ivec3_add(a: &RnIVec3, b: &RnIVec3) -> VmResult<RnIVec3> { checked(a.0, b.0, i32::checked_add, "Addition") }
+
+#[rune::function(instance, protocol = SUB)]
+fn ivec3_sub(a: &RnIVec3, b: &RnIVec3) -> VmResult<RnIVec3> { checked(a.0, b.0, i32::checked_sub, "Subtraction") }
+
+#[rune::function(instance, protocol = MUL)]
+fn ivec3_mul(a: &RnIVec3, b: &RnIVec3) -> VmResult<RnIVec3> { checked(a.0, b.0, i32::checked_mul, "Multiplication") }
+
+#[rune::function(instance, protocol = DIV)]
+/// Euclidean division (rounds to negative infinity), zero divisor is an error
+fn ivec3_div(a: &RnIVec3, b: &RnIVec3) -> VmResult<RnIVec3> { checked(a.0, b.0, i32::checked_div_euclid, "Division") }
+
+#[rune::function(instance, protocol = ADD_ASSIGN)]
+fn ivec3_add_assign(a: &mut RnIVec3, b: &RnIVec3) -> VmResult<()> {
+    *a = rune::vm_try!(checked(a.0, b.0, i32::checked_add, "Addition"));
+    VmResult::Ok(())
+}
+
+#[rune::function(instance, protocol = SUB_ASSIGN)]
+fn ivec3_sub_assign(a: &mut RnIVec3, b: &RnIVec3) -> VmResult<()> {
+    *a = rune::vm_try!(checked(a.0, b.0, i32::checked_sub, "Subtraction"));
+    VmResult::Ok(())
+}
+
+#[rune::function(instance, protocol = PARTIAL_EQ)]
+fn ivec3_partial_eq(a: &RnIVec3, b: &RnIVec3) -> bool { a == b }
+
+#[rune::function(instance, protocol = EQ)]
+fn ivec3_eq(a: &RnIVec3, b: &RnIVec3) -> bool { a == b }
+
+#[rune::function(instance, protocol = HASH)]
+fn ivec3_hash(value: &RnIVec3, hasher: &mut Hasher) { value.0.hash(hasher) }
+
+#[rune::function(instance, path = scale)]
+/// Multiply all components by scalar
+fn ivec3_scale(value: &RnIVec3, k: i32) -> VmResult<RnIVec3> {
+    checked(value.0, IVec3::splat(k), i32::checked_mul, "Multiplication")
+}
+
+#[rune::function(instance, path = distance)]
+fn ivec3_distance(a: &RnIVec3, b: &RnIVec3) -> f64 {
+    a.0.as_vec3().distance(b.0.as_vec3()) as f64
+}
+
+#[rune::function(instance, path = distance_squared)]
+fn ivec3_distance_squared(a: &RnIVec3, b: &RnIVec3) -> i32 {
+    a.0.distance_squared(b.0)
+}
+
+#[rune::function(instance, path = as_vec3)]
+fn ivec3_as_vec3(value: &RnIVec3) -> RnVec3 { RnVec3(value.0.as_vec3()) }
+
+#[rune::function]
+/// Position of chunk that contains world block position
+pub fn world_to_chunk(pos: &RnIVec3) -> RnIVec3 {
+    RnIVec3(pos.0.div_euclid(IVec3::splat(SIZE_I32)))
+}
+
+#[rune::function]
+/// Block position inside of it's chunk
+pub fn world_to_local(pos: &RnIVec3) -> RnIVec3 {
+    RnIVec3(pos.0.rem_euclid(IVec3::splat(SIZE_I32)))
+}
+
+#[rune::function]
+/// World position of the chunk's local block position
+pub fn local_to_world(chunk: &RnIVec3, local: &RnIVec3) -> RnIVec3 {
+    RnIVec3(chunk.0 * SIZE_I32 + local.0)
+}
+
+// ----------------------------------------------------------------------------------------------
+// Vec3 functions
+
+#[rune::function(instance, protocol = ADD)]
+fn vec3_add(a: &RnVec3, b: &RnVec3) -> RnVec3 { RnVec3(a.0 + b.0) }
+
+#[rune::function(instance, protocol = SUB)]
+fn vec3_sub(a: &RnVec3, b: &RnVec3) -> RnVec3 { RnVec3(a.0 - b.0) }
+
+#[rune::function(instance, protocol = MUL)]
+fn vec3_mul(a: &RnVec3, b: &RnVec3) -> RnVec3 { RnVec3(a.0 * b.0) }
+
+#[rune::function(instance, protocol = DIV)]
+fn vec3_div(a: &RnVec3, b: &RnVec3) -> RnVec3 { RnVec3(a.0 / b.0) }
+
+#[rune::function(instance, protocol = ADD_ASSIGN)]
+fn vec3_add_assign(a: &mut RnVec3, b: &RnVec3) { a.0 += b.0 }
+
+#[rune::function(instance, protocol = SUB_ASSIGN)]
+fn vec3_sub_assign(a: &mut RnVec3, b: &RnVec3) { a.0 -= b.0 }
+
+#[rune::function(instance, protocol = PARTIAL_EQ)]
+fn vec3_partial_eq(a: &RnVec3, b: &RnVec3) -> bool { a == b }
+
+#[rune::function(instance, path = scale)]
+fn vec3_scale(value: &RnVec3, k: f64) -> RnVec3 { RnVec3(value.0 * k as f32) }
+
+#[rune::function(instance, path = length)]
+fn vec3_length(value: &RnVec3) -> f64 { value.0.length() as f64 }
+
+#[rune::function(instance, path = normalize)]
+/// Normalized vector or zero
+fn vec3_normalize(value: &RnVec3) -> RnVec3 { RnVec3(value.0.normalize_or_zero()) }
+
+#[rune::function(instance, path = dot)]
+fn vec3_dot(a: &RnVec3, b: &RnVec3) -> f64 { a.0.dot(b.0) as f64 }
+
+#[rune::function(instance, path = distance)]
+fn vec3_distance(a: &RnVec3, b: &RnVec3) -> f64 { a.0.distance(b.0) as f64 }
+
+#[rune::function(instance, path = floor)]
+/// Block position that contains this point
+fn vec3_floor(value: &RnVec3) -> RnIVec3 { RnIVec3(value.0.floor().as_ivec3()) }
+
+/// Install vector types and functions
+pub fn install_vectors(m: &mut crate::api::ApiModule) -> rune::support::Result<()> {
+    m.ty::<RnIVec3>()?;
+    m.ty::<RnVec3>()?;
+
+    m.function(ivec3)?;
+    m.function(vec3)?;
+
+    // IVec3 fields
+    m.module().field_function(&Protocol::GET, "x", |v: &RnIVec3| v.0.x)?;
+    m.module().field_function(&Protocol::GET, "y", |v: &RnIVec3| v.0.y)?;
+    m.module().field_function(&Protocol::GET, "z", |v: &RnIVec3| v.0.z)?;
+    m.module().field_function(&Protocol::SET, "x", |v: &mut RnIVec3, x: i32| v.0.x = x)?;
+    m.module().field_function(&Protocol::SET, "y", |v: &mut RnIVec3, y: i32| v.0.y = y)?;
+    m.module().field_function(&Protocol::SET, "z", |v: &mut RnIVec3, z: i32| v.0.z = z)?;
+
+    // IVec3 operators
+    m.module().function_meta(ivec3_add)?;
+    m.module().function_meta(ivec3_sub)?;
+    m.module().function_meta(ivec3_mul)?;
+    m.module().function_meta(ivec3_div)?;
+    m.module().function_meta(ivec3_add_assign)?;
+    m.module().function_meta(ivec3_sub_assign)?;
+    m.module().function_meta(ivec3_partial_eq)?;
+    m.module().function_meta(ivec3_eq)?;
+    m.module().function_meta(ivec3_hash)?;
+
+    m.method::<RnIVec3>(ivec3_scale)?;
+    m.method::<RnIVec3>(ivec3_distance)?;
+    m.method::<RnIVec3>(ivec3_distance_squared)?;
+    m.method::<RnIVec3>(ivec3_as_vec3)?;
+
+    m.function(world_to_chunk)?;
+    m.function(world_to_local)?;
+    m.function(local_to_world)?;
+
+    // Vec3 fields
+    m.module().field_function(&Protocol::GET, "x", |v: &RnVec3| v.0.x as f64)?;
+    m.module().field_function(&Protocol::GET, "y", |v: &RnVec3| v.0.y as f64)?;
+    m.module().field_function(&Protocol::GET, "z", |v: &RnVec3| v.0.z as f64)?;
+    m.module().field_function(&Protocol::SET, "x", |v: &mut RnVec3, x: f64| v.0.x = x as f32)?;
+    m.module().field_function(&Protocol::SET, "y", |v: &mut RnVec3, y: f64| v.0.y = y as f32)?;
+    m.module().field_function(&Protocol::SET, "z", |v: &mut RnVec3, z: f64| v.0.z = z as f32)?;
+
+    // Vec3 operators
+    m.module().function_meta(vec3_add)?;
+    m.module().function_meta(vec3_sub)?;
+    m.module().function_meta(vec3_mul)?;
+    m.module().function_meta(vec3_div)?;
+    m.module().function_meta(vec3_add_assign)?;
+    m.module().function_meta(vec3_sub_assign)?;
+    m.module().function_meta(vec3_partial_eq)?;
+
+    m.method::<RnVec3>(vec3_scale)?;
+    m.method::<RnVec3>(vec3_length)?;
+    m.method::<RnVec3>(vec3_normalize)?;
+    m.method::<RnVec3>(vec3_dot)?;
+    m.method::<RnVec3>(vec3_distance)?;
+    m.method::<RnVec3>(vec3_floor)?;
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Events between the engine and scripts.
+//! Events are queued and delivered to active handlers of the same world on the next tick.
+
+use std::{sync::*, collections::*};
+use rune::runtime::VmResult;
+use crate::chunk::RnIVec3;
+use crate::math::*;
+
+static EVENTS: OnceLock<Events> = OnceLock::new();
+
+/// Engine or script event
+#[derive(Debug, Clone)]
+pub enum Event {
+    ChunkGenerated(IVec3),
+    ChunkUnloaded(IVec3),
+    MeshBuilt(IVec3),
+    /// Changed blocks box in world (inclusive), one event per chunk
+    BlockChanged(IVec3, IVec3),
+    /// Player identity
+    PlayerJoined(String),
+    /// Asset path
+    AssetChanged(String),
+    /// Script event: name and serialized (JSON) payload
+    Custom(String, String),
+}
+
+impl Event {
+    /// Event name used by handlers
+    pub fn name(&self) -> &str {
+        match self {
+            Self::ChunkGenerated(_) => "chunk_generated",
+            Self::ChunkUnloaded(_) => "chunk_unloaded",
+            Self::MeshBuilt(_) => "mesh_built",
+            Self::BlockChanged(..) => "block_changed",
+            Self::PlayerJoined(_) => "player_joined",
+            Self::AssetChanged(_) => "asset_changed",
+            Self::Custom(name, _) => name,
+        }
+    }
+
+    /// Convert event data into handler's argument
+    pub fn to_value(&self) -> rune::support::Result<rune::Value> {
+        Ok(match self {
+            Self::ChunkGenerated(pos)
+            | Self::ChunkUnloaded(pos)
+            | Self::MeshBuilt(pos) => rune::to_value(RnIVec3(*pos))?,
+            Self::BlockChanged(min, max) => rune::to_value((RnIVec3(*min), RnIVec3(*max)))?,
+            Self::PlayerJoined(value)
+            | Self::AssetChanged(value) => rune::to_value(value.clone())?,
+            Self::Custom(_, payload) => serde_json::from_str(payload)?,
+        })
+    }
+}
+
+/// Script function that handles event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handler {
+    pub script: String,
+    pub function: String,
+}
+
+#[derive(Debug)]
+struct Events {
+    /// Events emitted since last delivery with their world
+    queue: Mutex<Vec<(String, Event)>>,
+
+    /// Event name -> handlers
+    handlers: RwLock<HashMap<String, Vec<Handler>>>,
+}
+
+pub(crate) fn init_events() {
+    let queue = Mutex::new(Vec::new());
+    let handlers = RwLock::new(HashMap::new());
+
+    if EVENTS.set(Events { queue, handlers }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Emit event in the current world, it will be delivered on the next tick
+pub fn emit(event: Event) {
+    emit_in(crate::current_world(), event);
+}
+
+/// Emit event in the world
+pub fn emit_in(world: String, event: Event) {
+    let events = EVENTS.get().unwrap();
+    events.queue.lock().unwrap().push((world, event));
+}
+
+/// Take all queued events with their worlds
+pub(crate) fn take_events() -> Vec<(String, Event)> {
+    let events = EVENTS.get().unwrap();
+    std::mem::take(&mut *events.queue.lock().unwrap())
+}
+
+/// Get all event handlers
+pub(crate) fn handlers(name: &str) -> Vec<Handler> {
+    let events = EVENTS.get().unwrap();
+    let guard = events.handlers.read().unwrap();
+
+    guard.get(name).cloned().unwrap_or_default()
+}
+
+/// Register script event handler
+pub fn subscribe(name: String, handler: Handler) {
+    let events = EVENTS.get().unwrap();
+    let mut guard = events.handlers.write().unwrap();
+
+    let handlers = guard.entry(name).or_default();
+    if !handlers.contains(&handler) {
+        handlers.push(handler);
+    }
+}
+
+/// Remove all handlers of the script
+pub(crate) fn unsubscribe_script(script: &str) {
+    let events = EVENTS.get().unwrap();
+    let mut guard = events.handlers.write().unwrap();
+
+    for handlers in guard.values_mut() {
+        handlers.retain(|h| h.script != script);
+    }
+}
+
+/// Remove all handlers
+pub(crate) fn clear_handlers() {
+    let events = EVENTS.get().unwrap();
+    events.handlers.write().unwrap().clear();
+}
+
+#[rune::function]
+/// Register handler function of the calling script
+pub fn on_event(name: String, function: String) -> VmResult<()> {
+    let Some(script) = crate::current_script() else {
+        return VmResult::panic("Events are available only in scripts");
+    };
+
+    subscribe(name, Handler { script, function });
+    VmResult::Ok(())
+}
+
+#[rune::function(path = emit)]
+/// Emit custom script event
+pub fn emit_event(name: String, payload: rune::Value) -> VmResult<()> {
+    match serde_json::to_string(&payload) {
+        Ok(payload) => {
+            emit(Event::Custom(name, payload));
+            VmResult::Ok(())
+        },
+        Err(e) => VmResult::panic(format!("Event payload error: {}", e))
+    }
+}
@@ -3,7 +3,7 @@
 //! Morph: voxel engine with server-side mesher
 //! Mesher on Rune
 
-use std::{collections::*, io::Write, sync::*};
+use std::{cell::RefCell, collections::*, io::Write, sync::*, sync::atomic::*};
 
 // Re-exports
 pub use fastnoise_lite as noise;
@@ -12,15 +12,34 @@ pub use bevy_tasks as tasks;
 pub use rune;
 
 // Exports
+pub mod api;
 pub mod assets;
 pub mod chunk;
+pub mod events;
+pub mod lifecycle;
+pub mod logging;
 pub mod mesh;
+pub mod metrics;
+pub mod pipeline;
+pub mod queue;
+pub mod random;
+pub mod region_file;
+pub mod residency;
+pub mod roles;
+pub mod store;
+pub mod streaming;
+pub mod testing;
+pub mod timers;
+pub mod world;
+pub mod worldgen;
 
 use math::*;
 use tasks::*;
 
 use chunk::*;
 use mesh::*;
+use queue::WorkQueue;
+use roles::Role;
 
 /// Script metadata 
 #[derive(Debug, rune::Any)]
@@ -32,18 +51,72 @@ pub struct ScriptMeta {
     #[rune(set)]
     /// Count of one-time operations, one by default
     threading: u32,
+
+    /// Event handlers: event name and function
+    handlers: Vec<(String, String)>,
+
+    /// System by default
+    role: Role,
+
+    /// Pipeline stage of stage scripts
+    stage: Option<pipeline::Stage>,
+
+    /// World of the script, default world if not set
+    world: Option<String>,
 }
 
 impl Default for ScriptMeta {
     fn default() -> Self {
-        Self { threading: 1, entry: None }
+        Self { threading: 1, entry: None, handlers: Vec::new(), role: Role::System, stage: None, world: None }
+    }
+}
+
+impl ScriptMeta {
+    /// World id of the script
+    pub fn world_id(&self) -> &str {
+        self.world.as_deref().unwrap_or(DEFAULT_WORLD)
     }
 }
 
 #[rune::function]
 /// Create new script metadata
 pub fn meta(entry: String, threading: u32) -> ScriptMeta {
-    ScriptMeta { entry: Some(entry), threading }
+    ScriptMeta { entry: Some(entry), threading, ..Default::default() }
+}
+
+#[rune::function]
+/// Create block registry script metadata (without entry point)
+pub fn registry() -> ScriptMeta {
+    ScriptMeta { role: Role::BlockRegistry, ..Default::default() }
+}
+
+#[rune::function(instance)]
+/// Set script role
+pub fn role(mut meta: ScriptMeta, role: Role) -> ScriptMeta {
+    meta.role = role;
+    meta
+}
+
+#[rune::function(instance)]
+/// Set script role to pipeline stage
+pub fn stage(mut meta: ScriptMeta, stage: pipeline::Stage) -> ScriptMeta {
+    meta.role = Role::Stage;
+    meta.stage = Some(stage);
+    meta
+}
+
+#[rune::function(instance, path = world)]
+/// Set script world, it's created if not exists
+pub fn meta_world(mut meta: ScriptMeta, id: String) -> ScriptMeta {
+    meta.world = Some(id);
+    meta
+}
+
+#[rune::function(instance)]
+/// Add event handler to script metadata
+pub fn on(mut meta: ScriptMeta, event: String, handler: String) -> ScriptMeta {
+    meta.handlers.push((event, handler));
+    meta
 }
 
 // TODO: Script return data 
@@ -54,7 +127,17 @@ pub fn meta(entry: String, threading: u32) -> ScriptMeta {
 struct Script {
     unit: Arc<rune::Unit>,
     sources: Arc<rune::Sources>,
-    meta: ScriptMeta
+    meta: ScriptMeta,
+
+    /// Misconfigured scripts are not called
+    active: bool,
+}
+
+impl Script {
+    /// Core of the script world
+    fn core(&self) -> Arc<Core> {
+        create_world(self.meta.world_id())
+    }
 }
 
 static SCRIPTS: OnceLock<Scripts> = OnceLock::new();
@@ -69,7 +152,41 @@ struct Scripts {
     /// One-time tasks
     tasks: Mutex<HashMap<String, Vec<Task<()>>>>,
 
-    values: RwLock<HashMap<String, Script>>,
+    /// Ordered by path: stable ticking order
+    values: RwLock<BTreeMap<String, Script>>,
+}
+
+thread_local! {
+    /// Path and called function of the script executed on this thread
+    static CURRENT: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+/// Run closure in the script's context
+fn with_script<T>(path: &str, function: &str, f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT.replace(Some((path.to_string(), function.to_string())));
+    let result = f();
+    CURRENT.set(previous);
+
+    result
+}
+
+/// Run script function and record it's metrics
+fn timed<T, E>(path: &str, function: &str, f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let start = metrics::now();
+    let result = with_script(path, function, f);
+    metrics::record(path, metrics::now().saturating_sub(start), result.is_err());
+
+    result
+}
+
+/// Get path of the calling script
+pub fn current_script() -> Option<String> {
+    CURRENT.with_borrow(|source| source.as_ref().map(|(path, _)| path.clone()))
+}
+
+/// Get path and called function of the calling script
+pub fn current_source() -> Option<(String, String)> {
+    CURRENT.with_borrow(|source| source.clone())
 }
 
 /// Create scripts context and install main Morph module
@@ -80,7 +197,7 @@ fn init_scripts() -> rune::support::Result<()> {
     let runtime = Arc::new(context.runtime()?);
     let tasks = Mutex::new(HashMap::new());
 
-    let values = RwLock::new(HashMap::new());
+    let values = RwLock::new(BTreeMap::new());
 
     if SCRIPTS.set(Scripts { context, runtime, tasks, values }).is_err() {
         log::error!("Already initialized");
@@ -95,15 +212,63 @@ pub fn clear_scripts() {
 
     let mut guard = scripts.tasks.lock().unwrap();
     guard.clear();
+
+    events::clear_handlers();
+    timers::clear_timers();
+}
+
+/// Check if compiled unit has a function with this name
+fn has_function(runtime: &Arc<rune::runtime::RuntimeContext>, unit: &Arc<rune::Unit>, name: &str) -> bool {
+    let vm = rune::Vm::new(runtime.clone(), unit.clone());
+    vm.lookup_function([name]).is_ok()
+}
+
+/// Cancel script tasks that still running and drop finished ones
+fn drain_tasks(tasks: Vec<Task<()>>) {
+    for task in tasks {
+        if task.is_finished() { continue; }
+
+        // Waits only for the currently polled task
+        block_on(task.cancel());
+    }
+}
+
+/// Remove script, stop it's tasks and call `on_unload` hook.
+/// Script displaced by the removed one is activated unless it's replaced by a new version.
+/// Returns state value for a new script version
+fn unload_script(path: &String, replaced: bool) -> Option<rune::Value> {
+    let scripts = SCRIPTS.get().unwrap();
+
+    let script = {
+        let mut guard = scripts.values.write().unwrap();
+        guard.remove(path)?
+    };
+
+    // Tasks must be stopped before the hook is called
+    let tasks = scripts.tasks.lock().unwrap().remove(path);
+    drain_tasks(tasks.unwrap_or_default());
+    events::unsubscribe_script(path);
+
+    if !replaced {
+        reactivate(scripts, script.meta.role, script.meta.world_id());
+    }
+
+    if !has_function(&scripts.runtime, &script.unit, "on_unload") { return None; }
+
+    let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+    match with_script(path, "on_unload", || vm.call(["on_unload"], ())) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            log::warn!("Script unload error: {}", e);
+            None
+        }
+    }
 }
 
 /// Insert new script by type
 pub fn insert_script(path: String, raw: impl AsRef<str>) -> rune::support::Result<()> {
     let scripts = SCRIPTS.get().unwrap();
 
-    // Remove script if exists
-    remove_script(&path);
-
     let mut sources = rune::Sources::new();
     sources.insert(rune::Source::memory(raw)?)?;
 
@@ -111,6 +276,7 @@ pub fn insert_script(path: String, raw: impl AsRef<str>) -> rune::support::Resul
         .with_context(&scripts.context)
         .build();
 
+    // Old version keeps working if new one can't be compiled
     let unit = match result {
         Ok(unit) => Arc::new(unit),
         Err(e) => {
@@ -121,49 +287,154 @@ pub fn insert_script(path: String, raw: impl AsRef<str>) -> rune::support::Resul
         }        
     };
 
+    // Role of the old version: it's place can be taken by another script
+    let previous = scripts.values.read().unwrap()
+        .get(&path)
+        .map(|s| (s.meta.role, s.meta.world_id().to_string()));
+
+    // Unload old version if exists and take it's state
+    let state = unload_script(&path, true);
+
+    // Scheduled calls survive if function still exists
+    timers::retain_script(&path, |function| has_function(&scripts.runtime, &unit, function));
+
     let sources = Arc::new(sources);
     let mut vm = rune::Vm::new(scripts.runtime.clone(), unit.clone());
 
     // Init scripts and get metadata
-    let result = vm.call(["init"], ())
-        .and_then(|v| Ok(rune::from_value::<ScriptMeta>(v)?));
-
-    let meta = match result {
-        Ok(meta) => meta,
+    let meta = match with_script(&path, "init", || vm.call(["init"], ())) {
+        Ok(value) => match rune::from_value::<ScriptMeta>(value) {
+            Ok(meta) => meta,
+            Err(_) => {
+                log::error!("Script {}: init must return meta(entry, threading) or registry()", path);
+                ScriptMeta::default()
+            }
+        },
         Err(e) => { 
-            log::warn!("Script init error: {}", e);
+            log::error!("Script {} init error: {}", path, e);
             ScriptMeta::default()
         }
     };
-        
-    // Don't add script if it don't have entry point
-    if meta.entry.is_none() { return Ok(()); }
+
+    let checked = roles::validate(&unit, meta.role, meta.entry.as_deref())
+        .and_then(|()| roles::validate_stage(meta.role, meta.stage));
+
+    let active = match checked {
+        Ok(()) => match active_role(scripts, meta.role, meta.world_id()) {
+            Some(other) if meta.role.is_exclusive() => {
+                log::error!("Script {} ({:?}): {} is already active", path, meta.role, other);
+                false
+            },
+            _ => true
+        },
+        Err(message) => {
+            log::error!("Script {} ({:?}): {}", path, meta.role, message);
+            false
+        }
+    };
+
+    // World of the active script exists while it's loaded
+    if active {
+        create_world(meta.world_id());
+    }
+
+    // Handlers from metadata
+    for (event, function) in meta.handlers.iter().cloned() {
+        events::subscribe(event, events::Handler { script: path.clone(), function });
+    }
+
+    // Hand-off previous version state
+    if let Some(state) = state && has_function(&scripts.runtime, &unit, "on_reload")
+        && let Err(e) = with_script(&path, "on_reload", || vm.call(["on_reload"], (state,))) {
+        log::warn!("Script reload error: {}", e);
+    }
 
     // Create tasks variable
     let mut tasks = scripts.tasks.lock().unwrap();
     tasks.insert(path.clone(), Vec::new());
 
+    // Inactive scripts are kept for lifecycle hooks
     let mut guard = scripts.values.write().unwrap();
-    guard.insert(path, Script { unit, meta, sources });
+    guard.insert(path, Script { unit, meta, sources, active });
+    drop(guard);
+
+    // New version can leave the role of the old one
+    if let Some((role, world)) = previous {
+        reactivate(scripts, role, &world);
+    }
 
     Ok(())
 }
 
-/// Insert new script by type
-pub fn remove_script(path: &String) {
-    let scripts = SCRIPTS.get().unwrap();
+/// Activate first valid script displaced from the exclusive role if the role is free
+fn reactivate(scripts: &Scripts, role: Role, world: &str) {
+    if !role.is_exclusive() || active_role(scripts, role, world).is_some() { return; }
 
     let mut guard = scripts.values.write().unwrap();
+    let displaced = guard.iter_mut().find(|(_, s)| {
+        !s.active && s.meta.role == role && s.meta.world_id() == world
+            && roles::validate(&s.unit, s.meta.role, s.meta.entry.as_deref()).is_ok()
+            && roles::validate_stage(s.meta.role, s.meta.stage).is_ok()
+    });
+
+    let Some((path, script)) = displaced else { return };
+    log::info!("Script {} ({:?}) is active", path, role);
+    script.active = true;
+
+    drop(guard);
+    create_world(world);
+}
 
-    // Remove tasks if exists
-    if let Some(_) = guard.remove(path) {
-        let mut tasks = scripts.tasks.lock().unwrap();
-        tasks.remove(path);
-    }
+/// Path of the active script of the world with this role
+fn active_role(scripts: &Scripts, role: Role, world: &str) -> Option<String> {
+    let guard = scripts.values.read().unwrap();
+
+    guard.iter()
+        .find(|(_, s)| s.active && s.meta.role == role && s.meta.world_id() == world)
+        .map(|(path, _)| path.clone())
+}
+
+/// Pipeline stages done by active scripts of the current world
+pub(crate) fn stage_scripts() -> HashSet<pipeline::Stage> {
+    let scripts = SCRIPTS.get().unwrap();
+    let guard = scripts.values.read().unwrap();
+    let world = current_world();
+
+    guard.values()
+        .filter(|s| s.active && s.meta.role == Role::Stage && s.meta.world_id() == world)
+        .filter_map(|s| s.meta.stage)
+        .collect()
+}
+
+/// Call command script by it's entry name, returns false if command not found
+pub fn run_command(name: &str, args: Vec<String>) -> rune::support::Result<bool> {
+    let scripts = SCRIPTS.get().unwrap();
+    let guard = scripts.values.read().unwrap();
+
+    let command = guard.iter().find(|(_, s)| {
+        s.active && s.meta.role == Role::Command && s.meta.entry.as_deref() == Some(name)
+    });
+
+    let Some((path, script)) = command else { return Ok(false) };
+
+    let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+    with_world(&script.core(), || timed(path, name, || vm.call([name], (args,))))?;
+
+    Ok(true)
+}
+
+/// Remove script by path
+pub fn remove_script(path: &String) {
+    // State is dropped: there is no next version
+    let _ = unload_script(path, false);
+    timers::remove_script(path);
+    metrics::remove_script(path);
 }
 
 pub async fn run_script(
     runtime: Arc<rune::runtime::RuntimeContext>, 
+    world: Arc<Core>,
+    path: String,
     entry: String,
     unit: Arc<rune::Unit>, 
     sources: Arc<rune::Sources>
@@ -174,7 +445,11 @@ pub async fn run_script(
     let mut buffer = String::new();
 
     let mut diag = rune::Diagnostics::new();
-    if let Err(_) = vm.call_with_diagnostics([entry.as_str()], (), Some(&mut diag)) {
+    let result = with_world(&world, || timed(&path, &entry, || {
+        vm.call_with_diagnostics([entry.as_str()], (), Some(&mut diag))
+    }));
+
+    if result.is_err() {
         diag.emit(&mut stream, &sources).unwrap();
         // Safety: output stream is always UTF-8
         unsafe { stream.write_all(buffer.as_bytes_mut()).unwrap(); }
@@ -183,20 +458,147 @@ pub async fn run_script(
     }
 }
 
+/// Deliver queued events to scripts handlers
+fn dispatch_events(scripts: &Scripts) {
+    let guard = scripts.values.read().unwrap();
+
+    for (world, event) in events::take_events() {
+        for handler in events::handlers(event.name()) {
+            let Some(script) = guard.get(&handler.script) else { continue };
+
+            // Events of other worlds are not script's business, as timers
+            if !script.active || script.meta.world_id() != world { continue; }
+
+            let value = match event.to_value() {
+                Ok(value) => value,
+                Err(e) => {
+                    log::error!("Event {} value error for {}: {}", event.name(), handler.script, e);
+                    continue;
+                }
+            };
+
+            let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+            let function = handler.function.as_str();
+
+            let result = with_world(&script.core(), || {
+                timed(&handler.script, function, || vm.call([function], (value,)))
+            });
+
+            if let Err(e) = result {
+                log::error!("Event handler {} error: {}", function, e);
+            }
+        }
+    }
+}
+
+/// Call scheduled scripts functions
+fn run_timers(scripts: &Scripts) {
+    let guard = scripts.values.read().unwrap();
+
+    for (path, timer) in timers::advance() {
+        let Some(script) = guard.get(&path) else { continue };
+
+        let args = match serde_json::from_str::<Vec<rune::Value>>(&timer.args) {
+            Ok(args) => args,
+            Err(e) => {
+                log::error!("Scheduled {} arguments error: {}", timer.function, e);
+                continue;
+            }
+        };
+
+        let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+        let function = timer.function.as_str();
+
+        let result = with_world(&script.core(), || timed(&path, function, || vm.call([function], args)));
+        if let Err(e) = result {
+            log::error!("Scheduled {} error: {}", function, e);
+        }
+    }
+}
+
+/// Scripts are called sequentially in the tick thread
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Enable deterministic scheduling with random seed or disable it.
+/// Same seed produces same world
+pub fn set_deterministic(seed: Option<u64>) {
+    DETERMINISTIC.store(seed.is_some(), Ordering::Release);
+    let Some(seed) = seed else { return };
+
+    random::set_seed(seed);
+
+    // Running tasks can't be ordered
+    let scripts = SCRIPTS.get().unwrap();
+    let mut guard = scripts.tasks.lock().unwrap();
+    for tasks in guard.values_mut() {
+        drain_tasks(std::mem::take(tasks));
+    }
+}
+
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Acquire)
+}
+
 /// Call all tickers scrits
 pub fn tick_scripts() -> rune::support::Result<()> {
     let scripts = SCRIPTS.get().unwrap();
     let runtime = scripts.runtime.clone();
     let taskpool = AsyncComputeTaskPool::get();
 
+    // Events emitted during previous tick
+    dispatch_events(scripts);
+    run_timers(scripts);
+
+    // Worlds where native generator replaces missing or broken generator script
+    let mut natives = HashSet::new();
+
+    for world in worlds() {
+        with_world(&world, || {
+            // Fill generator and mesher queues around load centres
+            streaming::update();
+            pipeline::update();
+
+            if worldgen::is_native_forced() || active_role(scripts, Role::Generator, world.id()).is_none() {
+                run_native_generator();
+                natives.insert(world.id().to_string());
+            }
+        });
+    }
+
+    // Memory budget is shared by all worlds
+    residency::enforce_budget();
+
     let guard = scripts.values.read().unwrap();
     let mut tasks_guard = scripts.tasks.lock().unwrap();
+    let deterministic = is_deterministic();
 
     for (path, script) in guard.iter() {
+        if !script.active || !script.meta.role.is_ticked() { continue; }
+        if script.meta.role == Role::Generator && natives.contains(script.meta.world_id()) { continue; }
+
+        // Script without entry point is not ticked
+        let Some(entry) = script.meta.entry.clone() else { continue };
+
+        let world = script.core();
+
+        // All operations one by one in the current thread
+        if deterministic {
+            metrics::record_tasks(path, 0, script.meta.threading);
+
+            for _ in 0..script.meta.threading {
+                let unit = script.unit.clone();
+                let sources = script.sources.clone();
+
+                block_on(run_script(runtime.clone(), world.clone(), path.clone(), entry.clone(), unit, sources));
+            }
+
+            continue;
+        }
+
         let tasks = tasks_guard.remove(path).unwrap();
         let count = script.meta.threading as usize;
 
-        let mut new = Vec::with_capacity(count as usize);
+        let mut new = Vec::with_capacity(count);
         for task in tasks {
             if !task.is_finished() {
                 new.push(task);
@@ -205,18 +607,19 @@ pub fn tick_scripts() -> rune::support::Result<()> {
             // todo: else block on task 
         }
 
+        metrics::record_tasks(path, new.len(), script.meta.threading);
+
         // Can task be spawned?
         if new.len() >= count { 
             tasks_guard.insert(path.clone(), new);
             continue; 
         }
 
-        let entry = script.meta.entry.clone().unwrap();
         let unit = script.unit.clone();
         let sources = script.sources.clone();
 
         // Spawn task and insert
-        new.push(taskpool.spawn(run_script(runtime.clone(), entry, unit, sources)));
+        new.push(taskpool.spawn(run_script(runtime.clone(), world, path.clone(), entry, unit, sources)));
 
         tasks_guard.insert(path.clone(), new);
     }
@@ -224,48 +627,173 @@ pub fn tick_scripts() -> rune::support::Result<()> {
     Ok(())
 }
 
-static CORE: OnceLock<Core> = OnceLock::new();
+/// Max chunks generated natively per tick
+const NATIVE_GENERATOR_BUDGET: usize = 16;
+
+fn run_native_generator() {
+    let centres = streaming::centre_positions();
+
+    for _ in 0..NATIVE_GENERATOR_BUDGET {
+        let Some(pos) = pop_gen(&centres) else { break };
+        insert_chunk(pos, worldgen::generate_native(pos));
+    }
+}
+
+/// World of scripts without world and server players by default
+pub const DEFAULT_WORLD: &str = "overworld";
+
+/// World id -> world core, ordered for stable ticking
+static WORLDS: OnceLock<RwLock<BTreeMap<String, Arc<Core>>>> = OnceLock::new();
+
+thread_local! {
+    /// World of the code executed on this thread
+    static WORLD: RefCell<Option<Arc<Core>>> = const { RefCell::new(None) };
+}
 
+/// World data: chunks, meshes, queues and config
 pub struct Core {
+    id: String,
+    config: RwLock<world::WorldConfig>,
+
     chunks: Mutex<HashMap<IVec3, Chunk>>,
     meshes: Mutex<HashMap<IVec3, Mesh>>,
+    /// Chunks columns biomes
+    biomes: Mutex<HashMap<IVec3, Vec<u16>>>,
 
-    gen_queue: Mutex<VecDeque<IVec3>>,
-    meshes_queue: Mutex<VecDeque<IVec3>>,
+    gen_queue: Mutex<WorkQueue>,
+    meshes_queue: Mutex<WorkQueue>,
 
-    //noise: Mutex<noise::FastNoiseLite>
+    streaming: streaming::Streaming,
+    pipeline: pipeline::Pipeline,
+    lifecycle: lifecycle::Lifecycle,
+    residency: residency::Residency,
+}
+
+impl Core {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            config: RwLock::new(world::WorldConfig::default()),
+
+            chunks: Mutex::new(HashMap::new()),
+            meshes: Mutex::new(HashMap::new()),
+            biomes: Mutex::new(HashMap::new()),
+
+            gen_queue: Mutex::new(WorkQueue::default()),
+            meshes_queue: Mutex::new(WorkQueue::default()),
+
+            streaming: Default::default(),
+            pipeline: Default::default(),
+            lifecycle: Default::default(),
+            residency: Default::default(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 pub fn is_initalized() -> bool {
-    CORE.get().is_some()
+    WORLDS.get().is_some()
+}
+
+/// Core of the current world, default world outside of worlds
+pub fn core() -> Arc<Core> {
+    match WORLD.with_borrow(|world| world.clone()) {
+        Some(core) => core,
+        None => get_world(DEFAULT_WORLD).expect("Default world is not created")
+    }
+}
+
+/// Id of the current world
+pub fn current_world() -> String {
+    core().id.clone()
+}
+
+pub fn get_world(id: &str) -> Option<Arc<Core>> {
+    WORLDS.get().unwrap().read().unwrap().get(id).cloned()
+}
+
+/// All worlds ordered by id
+pub fn worlds() -> Vec<Arc<Core>> {
+    WORLDS.get().unwrap().read().unwrap().values().cloned().collect()
+}
+
+pub fn world_ids() -> Vec<String> {
+    WORLDS.get().unwrap().read().unwrap().keys().cloned().collect()
+}
+
+/// Get world or create it with default config
+pub fn create_world(id: &str) -> Arc<Core> {
+    if let Some(core) = get_world(id) { return core; }
+
+    let (core, inserted) = {
+        let mut guard = WORLDS.get().unwrap().write().unwrap();
+        match guard.get(id) {
+            Some(core) => (core.clone(), false),
+            None => {
+                let core = Arc::new(Core::new(id));
+                guard.insert(id.to_string(), core.clone());
+                (core, true)
+            }
+        }
+    };
+
+    // Spawn anchor of default config, world created by another thread is configured there
+    if inserted {
+        with_world(&core, || world::apply(world::WorldConfig::default())).expect("Default world config error");
+    }
+    core
+}
+
+/// Run closure in the world
+pub fn with_world<T>(core: &Arc<Core>, f: impl FnOnce() -> T) -> T {
+    let previous = WORLD.replace(Some(core.clone()));
+    let result = f();
+    WORLD.set(previous);
+
+    result
+}
+
+/// Run closure in the world by id, it's created if not exists
+pub fn in_world<T>(id: &str, f: impl FnOnce() -> T) -> T {
+    with_world(&create_world(id), f)
 }
 
 /// Init main shared components and core data
 pub fn init() {
-    AsyncComputeTaskPool::get_or_init(|| TaskPool::new());
+    AsyncComputeTaskPool::get_or_init(TaskPool::new);
 
     init_blocks();
+    store::init_store();
+    events::init_events();
+    timers::init_timers();
+    metrics::init_metrics();
+    logging::init_logging();
+    random::init_random();
+    worldgen::init_structures();
+    worldgen::init_biomes();
+    pipeline::init_pipeline();
+    worldgen::init_carving();
+    worldgen::init_generators();
     init_scripts().expect("Scripts initialization error");
 
-    if CORE.set(Core {
-        chunks: Mutex::new(HashMap::new()),
-        meshes: Mutex::new(HashMap::new()),
-
-        gen_queue: Mutex::new(VecDeque::new()),
-        meshes_queue: Mutex::new(VecDeque::new()),
-        
-        //noise: Mutex::new(noise::FastNoiseLite::new())
-    }).is_err() {
+    if WORLDS.set(RwLock::new(BTreeMap::new())).is_err() {
         log::error!("Already initialized");
     }
+    create_world(DEFAULT_WORLD);
+
+    // Built-in stages, scripts of the stage replace them
+    pipeline::set_native(pipeline::Stage::Carving, worldgen::carve_stage);
 }
 
-impl Into<RnIVec3> for IVec3 {
-    fn into(self) -> RnIVec3 { RnIVec3(self) }
+impl From<IVec3> for RnIVec3 {
+    fn from(value: IVec3) -> Self { RnIVec3(value) }
 }
 
 pub fn add_chunk_raw(pos: IVec3) -> Option<Chunk> {
-    let core = CORE.get().unwrap();
+    let core = core();
     let guard = core.chunks.lock().unwrap();
 
     guard.get(&pos).cloned()
@@ -273,12 +801,178 @@ pub fn add_chunk_raw(pos: IVec3) -> Option<Chunk> {
 
 /// Get chunk manually
 pub fn _get_chunk(pos: IVec3) -> Option<Chunk> {
-    let core = CORE.get().unwrap();
+    let core = core();
     let guard = core.chunks.lock().unwrap();
 
     guard.get(&pos).cloned()
 }
 
+/// Is chunk resident, without copying it
+pub fn has_chunk(pos: IVec3) -> bool {
+    core().chunks.lock().unwrap().contains_key(&pos)
+}
+
+/// Add generated chunk manually
+pub fn insert_chunk(pos: IVec3, chunk: Chunk) {
+    let core = core();
+    let mut guard = core.chunks.lock().unwrap();
+
+    // Structures parts waiting for this chunk
+    worldgen::apply_pending(pos, &chunk);
+    chunk.place(&core.id, pos);
+
+    if let Some(old) = guard.insert(pos, chunk.clone()) && !old.same(&chunk) {
+        old.detach();
+    }
+    core.gen_queue.lock().unwrap().complete(pos);
+    lifecycle::set_state(pos, lifecycle::ChunkState::Generated);
+    pipeline::reset(pos);
+    residency::touch(pos);
+
+    let mut biomes = core.biomes.lock().unwrap();
+    biomes.entry(pos).or_insert_with(|| worldgen::biome_map(pos));
+    drop(biomes);
+    residency::mark_dirty(&core, pos);
+
+    events::emit(events::Event::ChunkGenerated(pos));
+}
+
+/// Put stored chunk back without generation, returns false if data is corrupted.
+/// Resident chunk is newer than stored one and is kept
+pub fn restore_chunk(stored: residency::StoredChunk) -> bool {
+    let pos = stored.pos;
+    if has_chunk(pos) { return true; }
+
+    let Some(raw) = RawChunk::from_bytes(stored.data) else { return false };
+
+    let core = core();
+    let chunk = Chunk::new(raw);
+
+    // Structures parts placed while chunk was stored
+    worldgen::apply_pending(pos, &chunk);
+    chunk.place(&core.id, pos);
+    core.chunks.lock().unwrap().insert(pos, chunk);
+
+    let biomes = match stored.biomes.len() == SIZE * SIZE {
+        true => stored.biomes,
+        false => worldgen::biome_map(pos)
+    };
+    core.biomes.lock().unwrap().insert(pos, biomes);
+
+    let stage = pipeline::Stage::ALL.get(stored.stage as usize).copied().unwrap_or(pipeline::Stage::Terrain);
+    pipeline::restore(pos, stage);
+    core.gen_queue.lock().unwrap().complete(pos);
+    lifecycle::set_state(pos, lifecycle::ChunkState::Generated);
+
+    true
+}
+
+/// Remove chunk with it's mesh, returns data for storage
+pub fn remove_chunk(pos: IVec3) -> Option<residency::StoredChunk> {
+    let core = core();
+    let chunk = core.chunks.lock().unwrap().remove(&pos)?;
+    chunk.detach();
+
+    core.meshes.lock().unwrap().remove(&pos);
+    core.meshes_queue.lock().unwrap().complete(pos);
+    let biomes = core.biomes.lock().unwrap().remove(&pos).unwrap_or_default();
+
+    let stage = pipeline::get_stage(pos).map(|s| s.index() as u8).unwrap_or(0);
+    pipeline::forget(pos);
+    lifecycle::set_state(pos, lifecycle::ChunkState::Unloaded);
+
+    events::emit(events::Event::ChunkUnloaded(pos));
+
+    let (world, data) = (core.id.clone(), chunk.read().as_bytes().to_vec());
+    Some(residency::StoredChunk { world, pos, data, biomes, stage })
+}
+
+/// Copy of resident chunk data for storage, chunk stays in the world
+pub fn snapshot_chunk(pos: IVec3) -> Option<residency::StoredChunk> {
+    let core = core();
+    let data = core.chunks.lock().unwrap().get(&pos)?.read().as_bytes().to_vec();
+    let biomes = core.biomes.lock().unwrap().get(&pos).cloned().unwrap_or_default();
+
+    let stage = pipeline::get_stage(pos).map(|s| s.index() as u8).unwrap_or(0);
+    Some(residency::StoredChunk { world: core.id.clone(), pos, data, biomes, stage })
+}
+
+/// Positions of all resident chunks
+pub fn chunk_positions() -> Vec<IVec3> {
+    let core = core();
+    core.chunks.lock().unwrap().keys().copied().collect()
+}
+
+/// Resident chunks count, meshes count and meshes bytes
+pub(crate) fn core_sizes() -> (usize, usize, usize) {
+    let core = core();
+    let chunks = core.chunks.lock().unwrap().len();
+
+    let meshes = core.meshes.lock().unwrap();
+    let bytes = meshes.values()
+        .map(|m| (m.vertices().len() + m.indices().len()) * std::mem::size_of::<u32>())
+        .sum();
+
+    (chunks, meshes.len(), bytes)
+}
+
+/// Get chunk biomes map manually (index: x + z * SIZE)
+pub fn _get_biomes(pos: IVec3) -> Option<Vec<u16>> {
+    let core = core();
+    let guard = core.biomes.lock().unwrap();
+
+    guard.get(&pos).cloned()
+}
+
+/// Get mesh manually
+pub fn _get_mesh(pos: IVec3) -> Option<Mesh> {
+    let core = core();
+    let guard = core.meshes.lock().unwrap();
+
+    guard.get(&pos).cloned()
+}
+
+/// Is chunk mesh built, without copying it
+pub fn has_mesh(pos: IVec3) -> bool {
+    core().meshes.lock().unwrap().contains_key(&pos)
+}
+
+/// Add chunk position to generator queue (cancelled out of view)
+pub fn push_gen(pos: IVec3) {
+    let core = core();
+    if core.gen_queue.lock().unwrap().push(pos, false) {
+        lifecycle::set_state(pos, lifecycle::ChunkState::Queued);
+    }
+}
+
+/// Add chunk position to mesher queue (cancelled out of view)
+pub fn push_mesh(pos: IVec3) {
+    let core = core();
+    core.meshes_queue.lock().unwrap().push(pos, false);
+}
+
+/// Take nearest to centres position from generator queue
+fn pop_gen(centres: &[IVec3]) -> Option<IVec3> {
+    let core = core();
+    let pos = core.gen_queue.lock().unwrap().pop(timers::current_tick(), centres)?;
+
+    lifecycle::set_state(pos, lifecycle::ChunkState::Generating);
+    Some(pos)
+}
+
+/// Cancel queued positions out of view
+pub fn cancel_queued(visible: impl Fn(IVec3) -> bool) {
+    let core = core();
+    let tick = timers::current_tick();
+
+    let mut dropped = core.gen_queue.lock().unwrap().retain(tick, &visible);
+    dropped.extend(core.meshes_queue.lock().unwrap().retain(tick, &visible));
+
+    for pos in dropped {
+        lifecycle::revert(pos);
+    }
+}
+
 #[rune::macro_]
 fn f(
     cx: &mut rune::macros::MacroContext<'_, '_, '_>, 
@@ -313,116 +1007,284 @@ fn ident_to_string(cx: &mut MacroContext<'_, '_, '_>, stream: &TokenStream) -> c
 #[rune::function]
 fn debug(value: rune::Value) {
     match value.borrow_string_ref() {
-        Ok(output) => log::debug!("{}", output.to_string()),
+        Ok(output) => log::debug!("{}", &*output),
         Err(_) => log::debug!("{:?}", value)
     }
 }
 
-// todo: Noise for worldgen
+#[rune::function]
+/// Get chunk of the world by id, evicted chunk is loaded back from storage
+fn get_chunk(world: String, pos: &RnIVec3) -> Option<Chunk> {
+    let world = get_world(&world)?;
+
+    with_world(&world, || {
+        if !residency::load(pos.0) { return None; }
+        residency::touch(pos.0);
+
+        _get_chunk(pos.0)
+    })
+}
+
+#[rune::function(path = current_world)]
+/// World id of the calling script
+fn current_world_id() -> String {
+    current_world()
+}
+
+#[rune::function(path = worlds)]
+/// Ids of all worlds
+fn world_list() -> Vec<String> {
+    world_ids()
+}
 
 #[rune::function]
-fn get_chunk(pos: RnIVec3) -> Option<Chunk> {
-    _get_chunk(pos.0)
+fn add_chunk(chunk: Chunk, pos: &RnIVec3) {
+    insert_chunk(pos.0, chunk)
 }
 
 #[rune::function]
-fn add_chunk(chunk: Chunk, pos: RnIVec3) {
-    let core = CORE.get().unwrap();
-    let mut guard = core.chunks.lock().unwrap();
+/// Get biomes ids of the chunk columns
+fn chunk_biomes(pos: &RnIVec3) -> Option<Vec<u16>> {
+    _get_biomes(pos.0)
+}
+
+#[rune::function]
+fn get_mesh(pos: &RnIVec3) -> Option<Mesh> {
+    _get_mesh(pos.0)
+}
+
+#[rune::function]
+/// Add chunk position to generator queue (kept out of view)
+fn queue_gen(pos: &RnIVec3) {
+    let core = core();
+    if core.gen_queue.lock().unwrap().push(pos.0, true) {
+        lifecycle::set_state(pos.0, lifecycle::ChunkState::Queued);
+    }
+}
 
-    guard.insert(pos.0, chunk);
+#[rune::function]
+/// Add chunk position to mesher queue (kept out of view)
+fn queue_mesh(pos: &RnIVec3) {
+    let core = core();
+    core.meshes_queue.lock().unwrap().push(pos.0, true);
 }
 
 #[rune::function]
-/// Request chunk position from generator queue
+/// Request nearest to players chunk position from generator queue
 fn request_gen() -> Option<RnIVec3> {
-    let core = CORE.get().unwrap();
-    let mut queue = core.gen_queue.lock().unwrap();
-   
-    queue.pop_back().and_then(|p| Some(RnIVec3(p)))
+    pop_gen(&streaming::centre_positions()).map(RnIVec3)
 }
 
 #[rune::function]
-/// Request chunk position from mesher queue
+/// Request nearest to players chunk position from mesher queue
 fn request_mesh() -> Option<RnIVec3> {
-    let core = CORE.get().unwrap();
-    let mut queue = core.meshes_queue.lock().unwrap();
-    
-    queue.pop_back().and_then(|p| Some(RnIVec3(p)))
+    let centres = streaming::centre_positions();
+
+    let core = core();
+    let pos = core.meshes_queue.lock().unwrap().pop(timers::current_tick(), &centres)?;
+
+    lifecycle::set_state(pos, lifecycle::ChunkState::Meshing);
+    Some(RnIVec3(pos))
 }
 
 #[rune::function]
-/// Return mesh position back to the list
-fn return_mesh(pos: RnIVec3) {
-    let core = CORE.get().unwrap();
-    let mut queue = core.meshes_queue.lock().unwrap();
-    
-    queue.push_back(pos.0)
+/// Return not ready mesh position back to the queue, it's retried later
+fn return_mesh(pos: &RnIVec3) {
+    let core = core();
+    core.meshes_queue.lock().unwrap().retry(pos.0, timers::current_tick());
+
+    lifecycle::revert(pos.0);
 }
 
 #[rune::function]
 /// Add mesh to a core
 /// TODO: add position value to intermediate buffer 
-fn add_mesh(mesh: Mesh, pos: RnIVec3) {
-    let core = CORE.get().unwrap();
+fn add_mesh(mesh: Mesh, pos: &RnIVec3) {
+    let core = core();
     let mut meshes = core.meshes.lock().unwrap();
 
     meshes.insert(pos.0, mesh);
+    core.meshes_queue.lock().unwrap().complete(pos.0);
+    lifecycle::set_state(pos.0, lifecycle::ChunkState::Meshed);
+
+    events::emit(events::Event::MeshBuilt(pos.0));
 }
 
+/// Morph module constants
+pub const CONSTANTS: &[(&str, usize)] = &[
+    ("SIZE", SIZE),
+    ("SIZE_P3", SIZE_P3),
+];
+
 /// Setup module
 pub fn module(context: &mut rune::Context) -> rune::support::Result<()> {
-    let mut m = rune::Module::new();
+    morph_module()?.install(context)?;
+    Ok(())
+}
+
+/// Build Morph module with it's API reference
+pub fn morph_module() -> rune::support::Result<api::ApiModule> {
+    let mut m = api::ApiModule::new();
 
     // Constants
-    m.constant("SIZE", SIZE).build()?;
-    m.constant("SIZE_P3", SIZE_P3).build()?;
+    for (name, value) in CONSTANTS {
+        m.constant(name, *value)?;
+    }
 
     // Main types
-    m.ty::<ModelType>()?;
+    m.enum_ty::<ModelType>()?;
     m.ty::<Model>()?;
     m.ty::<BlockType>()?;
     m.ty::<Chunk>()?;
     m.ty::<ChunksRefs>()?;
-    m.ty::<Direction>()?;
+    m.ty::<Region>()?;
+    m.ty::<Blocks>()?;
+    m.enum_ty::<Direction>()?;
     m.ty::<Mesh>()?;
+    m.ty::<ScriptMeta>()?;
+    m.enum_ty::<Role>()?;
+    m.enum_ty::<lifecycle::ChunkState>()?;
+    m.enum_ty::<pipeline::Stage>()?;
+    m.ty::<world::WorldConfig>()?;
+    m.ty::<lifecycle::ChunkRecord>()?;
+    m.ty::<worldgen::Noise>()?;
+    m.ty::<worldgen::Biome>()?;
+    m.enum_ty::<worldgen::NoiseType>()?;
+    m.enum_ty::<worldgen::FractalType>()?;
+    m.enum_ty::<worldgen::WarpType>()?;
+
+    // Vectors math
+    chunk::install_vectors(&mut m)?;
 
     // Helpful functions
-    m.macro_meta(f)?;
+    m.module().macro_meta(f)?;
 
-    m.function_meta(chunk::ivec3)?;
-    m.function_meta(debug)?;
-    m.function_meta(meta)?;
+    m.function(debug)?;
+    m.function(meta)?;
+    m.function(registry)?;
+    m.method::<ScriptMeta>(role)?;
+    m.method::<ScriptMeta>(stage)?;
+    m.method::<ScriptMeta>(meta_world)?;
+    m.method::<ScriptMeta>(on)?;
 
     // Chunks functions
-    m.function_meta(new_chunk)?;
-    m.function_meta(get_chunk)?;
-    m.function_meta(add_chunk)?;
+    m.function(new_chunk)?;
+    m.function(get_chunk)?;
+    m.function(add_chunk)?;
+
+    m.method::<Chunk>(get_block)?;
+    m.method::<Chunk>(set_block)?;
 
-    m.function_meta(get_block)?;
-    m.function_meta(set_block)?;
+    m.function(get_refs)?;
+    m.method::<ChunksRefs>(refs_block)?;
 
-    m.function_meta(get_refs)?;
-    m.function_meta(refs_block)?;
+    // Bulk editing
+    m.function(region)?;
+    m.function(fill_box)?;
+    m.function(fill_layer)?;
+    m.function(replace)?;
+    m.function(copy_region)?;
+    m.function(paste_region)?;
+    m.function(set_blocks)?;
 
     // Blocks functions
-    m.function_meta(new_model)?;
-    m.function_meta(clear_blocks)?;
-    m.function_meta(add_block)?;
-    m.function_meta(block_type)?;
-    m.function_meta(block_id)?;
-    m.function_meta(model_type)?;
-    m.function_meta(position_index)?;
+    m.function(new_model)?;
+    m.function(clear_blocks)?;
+    m.function(add_block)?;
+    m.function(set_replaceable)?;
+    m.function(block_type)?;
+    m.function(block_id)?;
+    m.function(model_type)?;
+    m.function(position_index)?;
+
+    // Noise
+    m.function(worldgen::new_noise)?;
+    m.method::<worldgen::Noise>(worldgen::fractal)?;
+    m.method::<worldgen::Noise>(worldgen::domain_warp)?;
+    m.method::<worldgen::Noise>(worldgen::get2)?;
+    m.method::<worldgen::Noise>(worldgen::get3)?;
+    m.method::<worldgen::Noise>(worldgen::fill_heightmap)?;
+
+    // Biomes
+    m.function(worldgen::new_biome)?;
+    m.method::<worldgen::Biome>(worldgen::biome_decorations)?;
+    m.function(worldgen::clear_biomes)?;
+    m.function(worldgen::add_biome)?;
+    m.function(worldgen::biome)?;
+    m.function(worldgen::biome_name)?;
+    m.function(worldgen::biome_at)?;
+    m.function(chunk_biomes)?;
+
+    // Native generators
+    m.function(worldgen::flat_chunk)?;
+    m.function(worldgen::terrain_chunk)?;
+
+    // Caves
+    m.function(worldgen::carve_noise_caves)?;
+    m.function(worldgen::carve_worm_caves)?;
+
+    // Structures
+    m.function(worldgen::place_structure)?;
 
     // Meshes
-    m.function_meta(new_mesh)?;
-    m.function_meta(add_mesh)?;
+    m.function(new_mesh)?;
+    m.method::<Mesh>(mesh_vertices)?;
+    m.method::<Mesh>(mesh_indices)?;
+    m.function(add_mesh)?;
+    m.function(get_mesh)?;
 
     // Requests to a Core
-    m.function_meta(request_gen)?;
-    m.function_meta(request_mesh)?;
-    m.function_meta(return_mesh)?;
-
-    context.install(m)?;
-    Ok(())
+    m.function(queue_gen)?;
+    m.function(queue_mesh)?;
+    m.function(request_gen)?;
+    m.function(request_mesh)?;
+    m.function(return_mesh)?;
+
+    // World
+    m.function(current_world_id)?;
+    m.function(world_list)?;
+    m.function(world::world_config)?;
+    m.method::<world::WorldConfig>(world::noise_seed)?;
+    m.method::<world::WorldConfig>(world::flat_layers)?;
+
+    // Streaming
+    m.function(streaming::add_anchor)?;
+    m.function(streaming::remove_anchor)?;
+
+    // Pipeline
+    m.function(pipeline::request_stage)?;
+    m.function(pipeline::complete_stage)?;
+    m.function(pipeline::return_stage)?;
+    m.function(pipeline::chunk_stage)?;
+
+    // Lifecycle
+    m.function(lifecycle::chunk_state)?;
+    m.function(lifecycle::chunk_record)?;
+
+    // Events
+    m.function(events::on_event)?;
+    m.function(events::emit_event)?;
+
+    // Scheduling
+    m.function(timers::schedule_after)?;
+    m.function(timers::schedule_every)?;
+    m.function(timers::cancel_schedule)?;
+
+    // Logging
+    m.function(logging::log_trace)?;
+    m.function(logging::log_info)?;
+    m.function(logging::log_warn)?;
+    m.function(logging::log_error)?;
+
+    // Random
+    m.function(random::random)?;
+    m.function(random::random_range)?;
+
+    // Scripts storage
+    m.function(store::store_get)?;
+    m.function(store::store_set)?;
+    m.function(store::store_remove)?;
+    m.function(store::store_keys)?;
+
+    Ok(m)
 }
@@ -0,0 +1,195 @@
+//! Chunks lifecycle: every known chunk position of the world has a state with validated
+//! transitions, ticks of changes and work counters.
+
+use std::{sync::*, collections::*};
+use crate::chunk::*;
+use crate::math::*;
+
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ChunkState {
+    /// Waiting in generator queue
+    #[rune(constructor)]
+    Queued,
+    /// Taken by generator
+    #[rune(constructor)]
+    Generating,
+    /// Blocks are ready, mesh is not
+    #[rune(constructor)]
+    Generated,
+    /// Taken by mesher
+    #[rune(constructor)]
+    Meshing,
+    #[rune(constructor)]
+    Meshed,
+    /// Was loaded, removed from memory
+    #[rune(constructor)]
+    Unloaded,
+}
+
+impl crate::api::ApiEnum for ChunkState {
+    const VARIANTS: &'static [Self] = &Self::ALL;
+}
+
+impl ChunkState {
+    pub const ALL: [ChunkState; 6] = [
+        Self::Queued, Self::Generating, Self::Generated, Self::Meshing, Self::Meshed, Self::Unloaded,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Generating => "generating",
+            Self::Generated => "generated",
+            Self::Meshing => "meshing",
+            Self::Meshed => "meshed",
+            Self::Unloaded => "unloaded",
+        }
+    }
+
+    /// Can chunk move into the state (`None` is unknown position)
+    pub fn can_enter(self, from: Option<ChunkState>) -> bool {
+        use ChunkState::*;
+
+        match self {
+            // Regeneration of existing chunk too
+            Queued => matches!(from, None | Some(Unloaded | Generating | Generated | Meshed)),
+            Generating => from == Some(Queued),
+            // Chunks can be added without queue, returned by mesher or loaded back (queued too)
+            Generated => matches!(from, None | Some(Queued | Generating | Unloaded | Meshing)),
+            Meshing => matches!(from, Some(Generated | Meshed)),
+            Meshed => matches!(from, Some(Generated | Meshing | Meshed)),
+            Unloaded => !matches!(from, None | Some(Unloaded)),
+        }
+    }
+}
+
+#[derive(rune::Any, Debug, Clone, Copy)]
+pub struct ChunkRecord {
+    #[rune(get, copy)]
+    pub state: ChunkState,
+    /// Tick of the first state
+    #[rune(get)]
+    pub created: u64,
+    /// Tick of the current state
+    #[rune(get)]
+    pub since: u64,
+
+    #[rune(get)]
+    pub transitions: u32,
+    #[rune(get)]
+    pub generations: u32,
+    #[rune(get)]
+    pub meshes: u32,
+}
+
+/// World chunks records
+#[derive(Debug, Default)]
+pub(crate) struct Lifecycle {
+    records: Mutex<HashMap<IVec3, ChunkRecord>>,
+}
+
+/// Move chunk into the state, invalid transition is not applied
+pub fn transition(pos: IVec3, state: ChunkState) -> Result<(), String> {
+    let core = crate::core();
+    let lifecycle = &core.lifecycle;
+    let mut guard = lifecycle.records.lock().unwrap();
+
+    let tick = crate::timers::current_tick();
+    let from = guard.get(&pos).map(|r| r.state);
+
+    if !state.can_enter(from) {
+        let from = from.map(|s| s.name()).unwrap_or("unknown");
+        return Err(format!("Chunk {} can't move from {} to {}", pos, from, state.name()));
+    }
+
+    let record = guard.entry(pos).or_insert(ChunkRecord {
+        state, created: tick, since: tick, transitions: 0, generations: 0, meshes: 0,
+    });
+
+    record.state = state;
+    record.since = tick;
+    record.transitions += 1;
+
+    match state {
+        // Returned mesh and storage load are not generations
+        ChunkState::Generated if !matches!(from, Some(ChunkState::Meshing | ChunkState::Unloaded)) => {
+            record.generations += 1
+        },
+        ChunkState::Meshed => record.meshes += 1,
+        _ => ()
+    }
+
+    Ok(())
+}
+
+/// Transition from engine code: invalid ones are only reported
+pub(crate) fn set_state(pos: IVec3, state: ChunkState) {
+    if let Err(e) = transition(pos, state) {
+        log::warn!("{}", e);
+    }
+}
+
+/// Queued or taken work is dropped: chunk returns to it's data state
+pub(crate) fn revert(pos: IVec3) {
+    let state = match (crate::has_chunk(pos), crate::has_mesh(pos)) {
+        (true, true) => ChunkState::Meshed,
+        (true, false) => ChunkState::Generated,
+        _ => ChunkState::Unloaded
+    };
+
+    let core = crate::core();
+    let lifecycle = &core.lifecycle;
+    let mut guard = lifecycle.records.lock().unwrap();
+
+    // Never loaded chunk is forgotten
+    let Some(record) = guard.get_mut(&pos) else { return };
+    if state == ChunkState::Unloaded && record.generations == 0 {
+        guard.remove(&pos);
+        return;
+    }
+
+    if record.state == state { return; }
+    if !state.can_enter(Some(record.state)) {
+        log::warn!("Chunk {} can't return from {} to {}", pos, record.state.name(), state.name());
+        return;
+    }
+
+    record.state = state;
+    record.since = crate::timers::current_tick();
+    record.transitions += 1;
+}
+
+pub fn get_record(pos: IVec3) -> Option<ChunkRecord> {
+    let core = crate::core();
+    let lifecycle = &core.lifecycle;
+    lifecycle.records.lock().unwrap().get(&pos).copied()
+}
+
+/// Count of chunks in every state in all worlds
+pub fn state_counts() -> Vec<(ChunkState, usize)> {
+    let mut counts = BTreeMap::new();
+
+    for world in crate::worlds() {
+        let guard = world.lifecycle.records.lock().unwrap();
+        for record in guard.values() {
+            *counts.entry(record.state).or_insert(0) += 1;
+        }
+    }
+
+    ChunkState::ALL.iter().map(|s| (*s, counts.get(s).copied().unwrap_or(0))).collect()
+}
+
+// ----------------------------------------------------------------------------------------------
+// Lifecycle functions
+
+#[rune::function]
+/// Chunk lifecycle state, `None` for unknown position
+pub fn chunk_state(pos: &RnIVec3) -> Option<ChunkState> {
+    get_record(pos.0).map(|r| r.state)
+}
+
+#[rune::function]
+/// Chunk lifecycle state with ticks and counters
+pub fn chunk_record(pos: &RnIVec3) -> Option<ChunkRecord> {
+    get_record(pos.0)
+}
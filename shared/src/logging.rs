@@ -0,0 +1,93 @@
+//! Scripts logging: records are tagged with script path and called function
+//! and rate-limited per script.
+//!
+//! Records have no source line: native functions have no access to the calling
+//! VM instruction, so the span of the log call is unknown.
+
+use std::{sync::*, collections::*};
+
+/// Max records of one script per tick
+const LIMIT: u32 = 32;
+
+static LOGGING: OnceLock<Logging> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct Limiter {
+    tick: u64,
+    count: u32,
+    /// Dropped records in the last limited tick
+    suppressed: u32,
+}
+
+#[derive(Debug)]
+struct Logging {
+    limits: Mutex<HashMap<String, Limiter>>,
+}
+
+pub(crate) fn init_logging() {
+    let limits = Mutex::new(HashMap::new());
+
+    if LOGGING.set(Logging { limits }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Check script records limit, returns count of records suppressed before
+fn allow(path: &str) -> Option<u32> {
+    let logging = LOGGING.get().unwrap();
+    let mut guard = logging.limits.lock().unwrap();
+
+    let tick = crate::timers::current_tick();
+    let limiter = guard.entry(path.to_string()).or_default();
+
+    let mut suppressed = 0;
+    if limiter.tick != tick {
+        suppressed = std::mem::take(&mut limiter.suppressed);
+        limiter.tick = tick;
+        limiter.count = 0;
+    }
+
+    if limiter.count >= LIMIT {
+        limiter.suppressed += 1;
+        return None;
+    }
+
+    limiter.count += 1;
+    Some(suppressed)
+}
+
+/// Write script record
+pub fn record(level: log::Level, value: rune::Value) {
+    let (path, function) = crate::current_source()
+        .unwrap_or((String::from("<native>"), String::new()));
+
+    let Some(suppressed) = allow(&path) else { return };
+    if suppressed > 0 {
+        log::warn!("[{}] {} log records suppressed", path, suppressed);
+    }
+
+    match value.borrow_string_ref() {
+        Ok(message) => log::log!(level, "[{}:{}] {}", path, function, &*message),
+        Err(_) => log::log!(level, "[{}:{}] {:?}", path, function, value)
+    }
+}
+
+#[rune::function]
+pub fn log_trace(value: rune::Value) {
+    record(log::Level::Trace, value)
+}
+
+#[rune::function]
+pub fn log_info(value: rune::Value) {
+    record(log::Level::Info, value)
+}
+
+#[rune::function]
+pub fn log_warn(value: rune::Value) {
+    record(log::Level::Warn, value)
+}
+
+#[rune::function]
+pub fn log_error(value: rune::Value) {
+    record(log::Level::Error, value)
+}
@@ -1,5 +1,3 @@
-use super::math::*;
-
 #[derive(rune::Any, Debug, Clone)]
 pub struct Mesh {
     #[rune(set)]
@@ -8,6 +6,16 @@ pub struct Mesh {
     indices: Vec<u32>,
 }
 
+impl Mesh {
+    pub fn vertices(&self) -> &[u32] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
 /// Create new mesh buffer
 #[rune::function]
 pub fn new_mesh() -> Mesh {
@@ -17,3 +25,15 @@ pub fn new_mesh() -> Mesh {
     }
 }
 
+
+#[rune::function(instance, path = vertices)]
+/// Copy of mesh vertices
+pub fn mesh_vertices(mesh: &Mesh) -> Vec<u32> {
+    mesh.vertices.clone()
+}
+
+#[rune::function(instance, path = indices)]
+/// Copy of mesh indices
+pub fn mesh_indices(mesh: &Mesh) -> Vec<u32> {
+    mesh.indices.clone()
+}
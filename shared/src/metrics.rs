@@ -0,0 +1,150 @@
+//! Per-script execution metrics and Core queues depths.
+//! Wall times are measured only on native targets: wasm modules (SpacetimeDB server)
+//! can't read a clock, reducer has only it's start timestamp.
+
+use std::{sync::*, collections::*};
+
+/// Count of the last durations used for percentiles
+const SAMPLES: usize = 1024;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct ScriptMetrics {
+    invocations: u64,
+    errors: u64,
+
+    total: u64,
+    min: u64,
+    max: u64,
+    /// Last durations ring buffer
+    samples: VecDeque<u64>,
+
+    live_tasks: usize,
+    threading: u32,
+}
+
+#[derive(Debug)]
+struct Metrics {
+    scripts: Mutex<HashMap<String, ScriptMetrics>>,
+}
+
+/// Script metrics snapshot, times in microseconds (zero on wasm)
+#[derive(Debug, Clone, Default)]
+pub struct ScriptStats {
+    pub path: String,
+    pub invocations: u64,
+    pub errors: u64,
+
+    pub min: u64,
+    pub avg: u64,
+    pub max: u64,
+    pub p99: u64,
+
+    pub live_tasks: usize,
+    pub threading: u32,
+}
+
+/// Core queues depths
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueStats {
+    pub gen_queue: usize,
+    pub meshes_queue: usize,
+}
+
+pub(crate) fn init_metrics() {
+    let scripts = Mutex::new(HashMap::new());
+
+    if METRICS.set(Metrics { scripts }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Current time in microseconds
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    static START: OnceLock<std::time::Instant> = OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed().as_micros() as u64
+}
+
+/// There is no clock on wasm: durations are zero
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 { 0 }
+
+/// Record one script invocation
+pub(crate) fn record(path: &str, duration: u64, is_error: bool) {
+    let metrics = METRICS.get().unwrap();
+    let mut guard = metrics.scripts.lock().unwrap();
+    let script = guard.entry(path.to_string()).or_default();
+
+    script.min = match script.invocations {
+        0 => duration,
+        _ => script.min.min(duration)
+    };
+    script.max = script.max.max(duration);
+    script.total += duration;
+    script.invocations += 1;
+    if is_error { script.errors += 1; }
+
+    if script.samples.len() == SAMPLES {
+        script.samples.pop_front();
+    }
+    script.samples.push_back(duration);
+}
+
+/// Update live tasks count of the script
+pub(crate) fn record_tasks(path: &str, live_tasks: usize, threading: u32) {
+    let metrics = METRICS.get().unwrap();
+    let mut guard = metrics.scripts.lock().unwrap();
+    let script = guard.entry(path.to_string()).or_default();
+
+    script.live_tasks = live_tasks;
+    script.threading = threading;
+}
+
+/// Remove script metrics
+pub(crate) fn remove_script(path: &str) {
+    let metrics = METRICS.get().unwrap();
+    metrics.scripts.lock().unwrap().remove(path);
+}
+
+/// Get all scripts metrics
+pub fn scripts_stats() -> Vec<ScriptStats> {
+    let metrics = METRICS.get().unwrap();
+    let guard = metrics.scripts.lock().unwrap();
+
+    let mut result = guard.iter().map(|(path, script)| {
+        let mut samples = script.samples.iter().cloned().collect::<Vec<u64>>();
+        samples.sort_unstable();
+
+        let p99 = match samples.len() {
+            0 => 0,
+            n => samples[(n * 99 / 100).min(n - 1)]
+        };
+
+        ScriptStats {
+            path: path.clone(),
+            invocations: script.invocations,
+            errors: script.errors,
+            min: script.min,
+            avg: script.total.checked_div(script.invocations).unwrap_or(0),
+            max: script.max,
+            p99,
+            live_tasks: script.live_tasks,
+            threading: script.threading,
+        }
+    }).collect::<Vec<ScriptStats>>();
+
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+/// Get queues depths of all worlds
+pub fn queue_stats() -> QueueStats {
+    let worlds = crate::worlds();
+
+    QueueStats {
+        gen_queue: worlds.iter().map(|w| w.gen_queue.lock().unwrap().len()).sum(),
+        meshes_queue: worlds.iter().map(|w| w.meshes_queue.lock().unwrap().len()).sum(),
+    }
+}
@@ -0,0 +1,261 @@
+//! World generation pipeline: terrain → carving → surface → features → lighting.
+//! Chunk advances to the stage only when all it's neighbours (3x3x3) reached the
+//! previous stage, so stages can write blocks across chunks borders.
+//! Stage is done by `Role::Stage` scripts or native function, stage without
+//! implementation is passed through.
+
+use std::{sync::*, collections::*};
+use rune::runtime::VmResult;
+use crate::chunk::*;
+use crate::math::*;
+use crate::queue::WorkQueue;
+
+/// Native stages are shared by all worlds
+static NATIVES: OnceLock<RwLock<HashMap<Stage, NativeStage>>> = OnceLock::new();
+
+/// Max native stages runs per tick
+const NATIVE_BUDGET: usize = 32;
+
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    /// Done by generator script
+    #[rune(constructor)]
+    Terrain,
+    #[rune(constructor)]
+    Carving,
+    #[rune(constructor)]
+    Surface,
+    #[rune(constructor)]
+    Features,
+    #[rune(constructor)]
+    Lighting,
+}
+
+impl crate::api::ApiEnum for Stage {
+    const VARIANTS: &'static [Self] = &Self::ALL;
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Self::Terrain, Self::Carving, Self::Surface, Self::Features, Self::Lighting,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn next(&self) -> Option<Stage> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.next().is_none()
+    }
+}
+
+/// Native stage implementation: chunk position and chunk
+pub type NativeStage = fn(IVec3, &Chunk);
+
+/// World pipeline state
+#[derive(Debug, Default)]
+pub(crate) struct Pipeline {
+    /// Chunk position -> last done stage
+    done: Mutex<HashMap<IVec3, Stage>>,
+    /// Positions ready for the stage, by stage index
+    queues: [Mutex<WorkQueue>; 5],
+}
+
+pub(crate) fn init_pipeline() {
+    if NATIVES.set(RwLock::new(HashMap::new())).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Set native stage implementation, it's used if no script of the world does the stage
+pub fn set_native(stage: Stage, f: NativeStage) {
+    NATIVES.get().unwrap().write().unwrap().insert(stage, f);
+}
+
+pub fn remove_native(stage: Stage) {
+    NATIVES.get().unwrap().write().unwrap().remove(&stage);
+}
+
+/// Last done stage of the chunk
+pub fn get_stage(pos: IVec3) -> Option<Stage> {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    pipeline.done.lock().unwrap().get(&pos).copied()
+}
+
+/// Chunk and all it's neighbours passed the pipeline
+pub fn is_complete(pos: IVec3) -> bool {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    let guard = pipeline.done.lock().unwrap();
+
+    ChunksRefs::OFFSETS.iter().all(|o| guard.get(&(pos + *o)).is_some_and(|s| s.is_last()))
+}
+
+/// Chunk is queued or taken for any stage
+pub fn in_work(pos: IVec3) -> bool {
+    let core = crate::core();
+    core.pipeline.queues.iter().any(|q| q.lock().unwrap().contains(pos))
+}
+
+/// Chunk blocks are (re)generated: pipeline starts over
+pub(crate) fn reset(pos: IVec3) {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    pipeline.done.lock().unwrap().insert(pos, Stage::Terrain);
+
+    for queue in &pipeline.queues {
+        queue.lock().unwrap().complete(pos);
+    }
+}
+
+/// Chunk is loaded from storage with it's done stage
+pub(crate) fn restore(pos: IVec3, stage: Stage) {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    pipeline.done.lock().unwrap().insert(pos, stage);
+}
+
+/// Chunk is removed from the world
+pub(crate) fn forget(pos: IVec3) {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    pipeline.done.lock().unwrap().remove(&pos);
+
+    for queue in &pipeline.queues {
+        queue.lock().unwrap().complete(pos);
+    }
+}
+
+/// Mark stage done, it must be the next stage of the chunk
+pub fn complete(pos: IVec3, stage: Stage) -> Result<(), String> {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    let mut guard = pipeline.done.lock().unwrap();
+
+    let Some(done) = guard.get_mut(&pos) else {
+        return Err(format!("Chunk {} is not in pipeline", pos));
+    };
+
+    if done.next() != Some(stage) {
+        return Err(format!("Chunk {} is at {:?}, can't complete {:?}", pos, done, stage));
+    }
+
+    *done = stage;
+    pipeline.queues[stage.index()].lock().unwrap().complete(pos);
+    crate::residency::mark_dirty(&core, pos);
+
+    Ok(())
+}
+
+/// All neighbours (3x3x3) reached the stage
+fn neighbours_reached(done: &HashMap<IVec3, Stage>, pos: IVec3, stage: Stage) -> bool {
+    for y in -1..=1 {
+        for z in -1..=1 {
+            for x in -1..=1 {
+                match done.get(&(pos + IVec3::new(x, y, z))) {
+                    Some(s) if *s >= stage => (),
+                    _ => return false
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Queue chunks ready for the next stages, run native and pass not implemented ones
+pub fn update() {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    let scripted = crate::stage_scripts();
+    let natives = NATIVES.get().unwrap().read().unwrap().clone();
+    let tick = crate::timers::current_tick();
+
+    // Ready chunks and their next stage, snapshot of the tick
+    let mut ready = {
+        let guard = pipeline.done.lock().unwrap();
+
+        guard.iter()
+            .filter_map(|(pos, done)| Some((*pos, *done, done.next()?)))
+            .filter(|(pos, done, _)| neighbours_reached(&guard, *pos, *done))
+            .map(|(pos, _, next)| (pos, next))
+            .collect::<Vec<_>>()
+    };
+    ready.sort_by_key(|(p, s)| (*s, p.y, p.z, p.x));
+
+    let mut budget = NATIVE_BUDGET;
+    for (pos, stage) in ready {
+        if scripted.contains(&stage) {
+            pipeline.queues[stage.index()].lock().unwrap().push(pos, false);
+            continue;
+        }
+
+        if let Some(f) = natives.get(&stage) {
+            if budget == 0 { continue; }
+            budget -= 1;
+
+            let Some(chunk) = crate::_get_chunk(pos) else { continue };
+            f(pos, &chunk);
+        }
+
+        pipeline.done.lock().unwrap().insert(pos, stage);
+        crate::residency::mark_dirty(&core, pos);
+    }
+
+    // Lost positions are queued again next tick
+    for queue in &pipeline.queues {
+        queue.lock().unwrap().retain(tick, |_| true);
+    }
+}
+
+/// Queued positions count by stage in all worlds
+pub fn queue_depths() -> Vec<(Stage, usize)> {
+    let worlds = crate::worlds();
+
+    Stage::ALL.iter().map(|s| {
+        let depth = worlds.iter().map(|w| w.pipeline.queues[s.index()].lock().unwrap().len()).sum();
+        (*s, depth)
+    }).collect()
+}
+
+// ----------------------------------------------------------------------------------------------
+// Pipeline functions
+
+#[rune::function]
+/// Request nearest to players chunk position ready for the stage
+pub fn request_stage(stage: Stage) -> Option<RnIVec3> {
+    let centres = crate::streaming::centre_positions();
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+
+    let mut queue = pipeline.queues[stage.index()].lock().unwrap();
+    queue.pop(crate::timers::current_tick(), &centres).map(RnIVec3)
+}
+
+#[rune::function]
+/// Mark the stage of the chunk done
+pub fn complete_stage(pos: &RnIVec3, stage: Stage) -> VmResult<()> {
+    match complete(pos.0, stage) {
+        Ok(()) => VmResult::Ok(()),
+        Err(e) => VmResult::panic(e)
+    }
+}
+
+#[rune::function]
+/// Return not ready position back to the stage queue, it's retried later
+pub fn return_stage(pos: &RnIVec3, stage: Stage) {
+    let core = crate::core();
+    let pipeline = &core.pipeline;
+    pipeline.queues[stage.index()].lock().unwrap().retry(pos.0, crate::timers::current_tick());
+}
+
+#[rune::function]
+/// Last done pipeline stage of the chunk
+pub fn chunk_stage(pos: &RnIVec3) -> Option<Stage> {
+    get_stage(pos.0)
+}
@@ -0,0 +1,192 @@
+//! Chunks work queues: every position is queued once and popped by distance to
+//! the nearest load centre. Not ready positions are returned with backoff,
+//! positions out of view are cancelled.
+
+use std::{cmp::Reverse, collections::*};
+use crate::math::*;
+
+/// Max retry delay in ticks
+const MAX_BACKOFF: u64 = 64;
+/// Taken position without result is considered lost after ticks
+const TAKEN_TIMEOUT: u64 = 600;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// Insertion order for equal distances
+    order: u64,
+    /// Tick since entry can be popped
+    ready_at: u64,
+    retries: u32,
+    /// Queued manually, not cancelled out of view
+    pinned: bool,
+}
+
+/// Heap key: distance or ready tick, insertion order and position
+type Key<T> = Reverse<(T, u64, [i32; 3])>;
+
+#[derive(Debug, Default)]
+pub struct WorkQueue {
+    entries: HashMap<IVec3, Entry>,
+    /// Popped positions in work: tick of pop and entry
+    taken: HashMap<IVec3, (u64, Entry)>,
+    order: u64,
+
+    /// Ready entries by distance to centres, stale keys are skipped on pop
+    ready: BinaryHeap<Key<i32>>,
+    /// Delayed entries by ready tick
+    delayed: BinaryHeap<Key<u64>>,
+    /// Centres of the ready heap distances
+    centres: Vec<IVec3>,
+}
+
+/// Squared distance to the nearest centre
+fn distance(pos: IVec3, centres: &[IVec3]) -> i32 {
+    centres.iter().map(|c| (pos - *c).length_squared()).min().unwrap_or(0)
+}
+
+impl WorkQueue {
+    /// Count of queued (not taken) positions
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Count of positions in work
+    pub fn taken(&self) -> usize {
+        self.taken.len()
+    }
+
+    /// Position is queued or in work
+    pub fn contains(&self, pos: IVec3) -> bool {
+        self.entries.contains_key(&pos) || self.taken.contains_key(&pos)
+    }
+
+    /// Position is queued or taken manually
+    pub fn is_pinned(&self, pos: IVec3) -> bool {
+        self.entries.get(&pos).or(self.taken.get(&pos).map(|(_, e)| e)).is_some_and(|e| e.pinned)
+    }
+
+    /// Position is popped and in work
+    pub fn is_taken(&self, pos: IVec3) -> bool {
+        self.taken.contains_key(&pos)
+    }
+
+    /// Queue position once, returns false if it's already queued or in work
+    pub fn push(&mut self, pos: IVec3, pinned: bool) -> bool {
+        if let Some(entry) = self.entries.get_mut(&pos) {
+            entry.pinned |= pinned;
+            return false;
+        }
+
+        if self.taken.contains_key(&pos) { return false; }
+
+        self.order += 1;
+        let entry = Entry { order: self.order, ready_at: 0, retries: 0, pinned };
+        self.entries.insert(pos, entry);
+        self.ready.push(Reverse((distance(pos, &self.centres), entry.order, pos.to_array())));
+
+        true
+    }
+
+    /// Entry of the heap key if it's not stale
+    fn current(&self, order: u64, pos: [i32; 3]) -> Option<(IVec3, Entry)> {
+        let pos = IVec3::from_array(pos);
+        self.entries.get(&pos).filter(|e| e.order == order).map(|e| (pos, *e))
+    }
+
+    /// Rebuild ready heap by new centres distances
+    fn rekey(&mut self, tick: u64, centres: &[IVec3]) {
+        self.centres = centres.to_vec();
+        self.ready = self.entries.iter()
+            .filter(|(_, e)| e.ready_at <= tick)
+            .map(|(p, e)| Reverse((distance(*p, centres), e.order, p.to_array())))
+            .collect();
+    }
+
+    /// Take ready position nearest to centres
+    pub fn pop(&mut self, tick: u64, centres: &[IVec3]) -> Option<IVec3> {
+        if self.centres != centres {
+            self.rekey(tick, centres);
+        }
+
+        // Delayed entries are ready now
+        while let Some(Reverse((ready_at, order, pos))) = self.delayed.peek().copied() {
+            if ready_at > tick { break; }
+            self.delayed.pop();
+
+            let Some((pos, entry)) = self.current(order, pos) else { continue };
+            if entry.ready_at != ready_at { continue; }
+
+            self.ready.push(Reverse((distance(pos, centres), order, pos.to_array())));
+        }
+
+        while let Some(Reverse((_, order, pos))) = self.ready.pop() {
+            let Some((pos, entry)) = self.current(order, pos) else { continue };
+            if entry.ready_at > tick { continue; }
+
+            self.entries.remove(&pos);
+            self.taken.insert(pos, (tick, entry));
+
+            return Some(pos);
+        }
+
+        None
+    }
+
+    /// Return not ready position, it's delayed exponentially by retries
+    pub fn retry(&mut self, pos: IVec3, tick: u64) {
+        if self.entries.contains_key(&pos) { return; }
+
+        let mut entry = match self.taken.remove(&pos) {
+            Some((_, entry)) => entry,
+            None => {
+                self.order += 1;
+                Entry { order: self.order, ready_at: 0, retries: 0, pinned: false }
+            }
+        };
+
+        entry.retries += 1;
+        entry.ready_at = tick + (1u64 << entry.retries.min(16)).min(MAX_BACKOFF);
+
+        self.entries.insert(pos, entry);
+        self.delayed.push(Reverse((entry.ready_at, entry.order, pos.to_array())));
+    }
+
+    /// Position work is done
+    pub fn complete(&mut self, pos: IVec3) {
+        self.taken.remove(&pos);
+        self.entries.remove(&pos);
+    }
+
+    /// Cancel not pinned positions out of view and forget lost ones, returns dropped positions
+    pub fn retain(&mut self, tick: u64, visible: impl Fn(IVec3) -> bool) -> Vec<IVec3> {
+        let mut dropped = Vec::new();
+
+        self.entries.retain(|pos, e| {
+            let keep = e.pinned || visible(*pos);
+            if !keep { dropped.push(*pos); }
+            keep
+        });
+
+        self.taken.retain(|pos, (taken, _)| {
+            let keep = tick.saturating_sub(*taken) < TAKEN_TIMEOUT;
+            if !keep { dropped.push(*pos); }
+            keep
+        });
+
+        // Drop stale keys of cancelled positions
+        if !dropped.is_empty() {
+            let centres = std::mem::take(&mut self.centres);
+            self.rekey(tick, &centres);
+            self.delayed = self.entries.iter()
+                .filter(|(_, e)| e.ready_at > tick)
+                .map(|(p, e)| Reverse((e.ready_at, e.order, p.to_array())))
+                .collect();
+        }
+
+        dropped
+    }
+}
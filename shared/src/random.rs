@@ -0,0 +1,91 @@
+//! Seeded per-script random numbers.
+//! Every script has own generator, so results don't depend on other scripts.
+
+use std::{sync::*, sync::atomic::*, collections::*};
+use rune::runtime::VmResult;
+
+static RANDOM: OnceLock<Random> = OnceLock::new();
+
+#[derive(Debug)]
+struct Random {
+    seed: AtomicU64,
+
+    /// Script path -> generator state
+    states: Mutex<HashMap<String, u64>>,
+}
+
+pub(crate) fn init_random() {
+    let seed = AtomicU64::new(0);
+    let states = Mutex::new(HashMap::new());
+
+    if RANDOM.set(Random { seed, states }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Set seed and restart all generators
+pub fn set_seed(seed: u64) {
+    let random = RANDOM.get().unwrap();
+    random.seed.store(seed, Ordering::Release);
+    random.states.lock().unwrap().clear();
+}
+
+pub fn seed() -> u64 {
+    RANDOM.get().unwrap().seed.load(Ordering::Acquire)
+}
+
+/// SplitMix64 step
+pub(crate) fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Stable (FNV-1a) hash of the script path mixed with seed
+fn script_seed(seed: u64, script: &str) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325u64;
+    for byte in script.bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3);
+    }
+
+    hash ^ seed
+}
+
+/// Next random number of the script generator
+pub fn next_u64(script: &str) -> u64 {
+    let random = RANDOM.get().unwrap();
+    let mut guard = random.states.lock().unwrap();
+
+    let state = guard.entry(script.to_string())
+        .or_insert_with(|| script_seed(seed(), script));
+
+    split_mix(state)
+}
+
+fn namespace() -> VmResult<String> {
+    match crate::current_script() {
+        Some(script) => VmResult::Ok(script),
+        None => VmResult::panic("Random is available only in scripts")
+    }
+}
+
+#[rune::function]
+/// Random number in 0..1 range
+pub fn random() -> VmResult<f64> {
+    let value = next_u64(&rune::vm_try!(namespace())) >> 11;
+    VmResult::Ok(value as f64 / (1u64 << 53) as f64)
+}
+
+#[rune::function]
+/// Random integer in min..max range
+pub fn random_range(min: i64, max: i64) -> VmResult<i64> {
+    let value = next_u64(&rune::vm_try!(namespace()));
+    if max <= min { return VmResult::Ok(min); }
+
+    // Span of the full i64 range doesn't fit into i64
+    let span = max.wrapping_sub(min) as u64;
+    VmResult::Ok(min.wrapping_add((value % span) as i64))
+}
@@ -0,0 +1,238 @@
+//! Region files: offline worlds format for backups, sharing and test fixtures.
+//! File groups 32x32x32 chunks: header, sorted offset table and zlib compressed
+//! chunk payloads. World directory contains `r.x.y.z.region` files.
+
+use std::{io::{Read, Write}, path::*, collections::*};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+use crate::chunk::*;
+use crate::math::*;
+use crate::residency::StoredChunk;
+
+/// Chunks along every region axis
+pub const REGION_SIZE: i32 = 32;
+pub const EXTENSION: &str = "region";
+
+const MAGIC: &[u8; 4] = b"MRGN";
+const VERSION: u8 = 1;
+
+/// Region position of the chunk
+pub fn region_pos(chunk: IVec3) -> IVec3 {
+    chunk.div_euclid(IVec3::splat(REGION_SIZE))
+}
+
+/// Chunk index in the region (x + z * 32 + y * 32²)
+fn local_index(chunk: IVec3) -> u16 {
+    let local = chunk.rem_euclid(IVec3::splat(REGION_SIZE));
+    (local.x + local.z * REGION_SIZE + local.y * REGION_SIZE.pow(2)) as u16
+}
+
+fn chunk_pos(region: IVec3, index: u16) -> IVec3 {
+    let i = index as i32;
+    let local = IVec3::new(i % REGION_SIZE, i / REGION_SIZE.pow(2), i / REGION_SIZE % REGION_SIZE);
+
+    region * REGION_SIZE + local
+}
+
+pub fn file_name(region: IVec3) -> String {
+    format!("r.{}.{}.{}.{}", region.x, region.y, region.z, EXTENSION)
+}
+
+/// Region position from `r.x.y.z.region` file name
+pub fn parse_file_name(name: &str) -> Option<IVec3> {
+    let name = name.strip_prefix("r.")?.strip_suffix(EXTENSION)?.strip_suffix('.')?;
+
+    let parts = name.split('.').map(|p| p.parse::<i32>().ok()).collect::<Option<Vec<_>>>()?;
+    let [x, y, z] = parts.as_slice() else { return None };
+
+    Some(IVec3::new(*x, *y, *z))
+}
+
+/// Take bytes from the front of the buffer
+fn take<'a>(bytes: &mut &'a [u8], count: usize) -> Result<&'a [u8], String> {
+    let (head, tail) = bytes.split_at_checked(count).ok_or("Unexpected end of data")?;
+    *bytes = tail;
+
+    Ok(head)
+}
+
+fn take_u16(bytes: &mut &[u8]) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(take(bytes, 2)?.try_into().unwrap()))
+}
+
+fn take_u32(bytes: &mut &[u8]) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap()))
+}
+
+/// Compressed chunk payload: stage, biomes count, biomes and raw chunk
+fn compress(chunk: &StoredChunk) -> std::io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+
+    encoder.write_all(&[chunk.stage])?;
+    encoder.write_all(&(chunk.biomes.len() as u16).to_le_bytes())?;
+    for biome in &chunk.biomes {
+        encoder.write_all(&biome.to_le_bytes())?;
+    }
+    encoder.write_all(&chunk.data)?;
+
+    encoder.finish()
+}
+
+fn decompress(world: &str, pos: IVec3, payload: &[u8]) -> Result<StoredChunk, String> {
+    let mut raw = Vec::new();
+    ZlibDecoder::new(payload).read_to_end(&mut raw).map_err(|e| e.to_string())?;
+
+    let mut bytes = raw.as_slice();
+    let stage = take(&mut bytes, 1)?[0];
+
+    let count = take_u16(&mut bytes)? as usize;
+    let biomes = (0..count).map(|_| take_u16(&mut bytes)).collect::<Result<Vec<_>, _>>()?;
+
+    if bytes.len() != BUF_SIZE {
+        return Err(format!("Chunk {} has {} bytes instead of {}", pos, bytes.len(), BUF_SIZE));
+    }
+
+    Ok(StoredChunk { world: world.to_string(), pos, data: bytes.to_vec(), biomes, stage })
+}
+
+/// Encode region file, all chunks must be in the region
+pub fn encode(region: IVec3, chunks: &[StoredChunk]) -> Result<Vec<u8>, String> {
+    let mut payloads = BTreeMap::new();
+    for chunk in chunks {
+        if region_pos(chunk.pos) != region {
+            return Err(format!("Chunk {} is out of region {}", chunk.pos, region));
+        }
+
+        payloads.insert(local_index(chunk.pos), compress(chunk).map_err(|e| e.to_string())?);
+    }
+
+    let mut result = Vec::new();
+    result.extend_from_slice(MAGIC);
+    result.push(VERSION);
+    result.extend_from_slice(&(payloads.len() as u32).to_le_bytes());
+
+    // Offset table: local index, payload offset and length
+    let mut offset = 0u32;
+    for (index, payload) in &payloads {
+        result.extend_from_slice(&index.to_le_bytes());
+        result.extend_from_slice(&offset.to_le_bytes());
+        result.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+
+        offset += payload.len() as u32;
+    }
+
+    for payload in payloads.values() {
+        result.extend_from_slice(payload);
+    }
+
+    Ok(result)
+}
+
+/// Decode all chunks of the region file
+pub fn decode(world: &str, region: IVec3, bytes: &[u8]) -> Result<Vec<StoredChunk>, String> {
+    let mut bytes = bytes;
+
+    if take(&mut bytes, MAGIC.len())? != MAGIC {
+        return Err(String::from("Not a region file"));
+    }
+
+    let version = take(&mut bytes, 1)?[0];
+    if version != VERSION {
+        return Err(format!("Unsupported region version {}", version));
+    }
+
+    let count = take_u32(&mut bytes)? as usize;
+    let mut entries = Vec::with_capacity(count.min(REGION_SIZE.pow(3) as usize));
+    for _ in 0..count {
+        entries.push((take_u16(&mut bytes)?, take_u32(&mut bytes)? as usize, take_u32(&mut bytes)? as usize));
+    }
+
+    // Rest of the file is payloads
+    let mut result = Vec::with_capacity(entries.len());
+    for (index, offset, length) in entries {
+        let pos = chunk_pos(region, index);
+        let payload = bytes.get(offset..offset + length)
+            .ok_or_else(|| format!("Chunk {} payload is out of file", pos))?;
+
+        result.push(decompress(world, pos, payload)?);
+    }
+
+    Ok(result)
+}
+
+/// Encode chunks into region files: world, region and file data
+pub fn export(chunks: impl IntoIterator<Item = StoredChunk>) -> Result<Vec<(String, IVec3, Vec<u8>)>, String> {
+    let mut regions: BTreeMap<(String, [i32; 3]), Vec<StoredChunk>> = BTreeMap::new();
+    for chunk in chunks {
+        let key = (chunk.world.clone(), region_pos(chunk.pos).to_array());
+        regions.entry(key).or_default().push(chunk);
+    }
+
+    let mut result = Vec::with_capacity(regions.len());
+    for ((world, region), chunks) in regions {
+        let region = IVec3::from_array(region);
+        let data = encode(region, &chunks)?;
+
+        result.push((world, region, data));
+    }
+
+    Ok(result)
+}
+
+/// Write chunks into worlds directories of the root, returns files count
+pub fn write_dir(root: impl AsRef<Path>, chunks: impl IntoIterator<Item = StoredChunk>) -> Result<usize, String> {
+    let root = root.as_ref();
+    let files = export(chunks)?;
+
+    for (world, region, data) in &files {
+        let dir = root.join(world);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Directory {} error: {}", dir.display(), e))?;
+
+        let path = dir.join(file_name(*region));
+        std::fs::write(&path, data).map_err(|e| format!("Region {} error: {}", path.display(), e))?;
+    }
+
+    Ok(files.len())
+}
+
+/// Read all region files of the world directory
+pub fn read_world(dir: impl AsRef<Path>, world: &str) -> Result<Vec<StoredChunk>, String> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Directory {} error: {}", dir.display(), e))?;
+
+    // Sorted for stable loading order
+    let mut files = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter_map(|p| Some((parse_file_name(p.file_name()?.to_str()?)?, p)))
+        .collect::<Vec<_>>();
+    files.sort_by_key(|(region, _)| region.to_array());
+
+    let mut result = Vec::new();
+    for (region, path) in files {
+        let bytes = std::fs::read(&path).map_err(|e| format!("Region {} error: {}", path.display(), e))?;
+        let chunks = decode(world, region, &bytes).map_err(|e| format!("Region {} error: {}", path.display(), e))?;
+
+        result.extend(chunks);
+    }
+
+    Ok(result)
+}
+
+/// Read all worlds of the root, every directory is a world
+pub fn read_dir(root: impl AsRef<Path>) -> Result<Vec<StoredChunk>, String> {
+    let root = root.as_ref();
+    let entries = std::fs::read_dir(root).map_err(|e| format!("Directory {} error: {}", root.display(), e))?;
+
+    let mut worlds = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .filter_map(|p| Some((p.file_name()?.to_str()?.to_string(), p)))
+        .collect::<Vec<_>>();
+    worlds.sort();
+
+    let mut result = Vec::new();
+    for (world, dir) in worlds {
+        result.extend(read_world(&dir, &world)?);
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,301 @@
+//! Chunks residency: chunks out of range of all world load centres are unloaded
+//! and resident memory of all worlds is kept in budget by evicting least recently
+//! used chunks. Chunks with queued or taken work are never evicted. Evicted and
+//! changed chunks are persisted through the storage outbox, evicted ones are
+//! loaded back when they are requested again.
+
+use std::{sync::*, sync::atomic::*, collections::*};
+use crate::chunk::*;
+use crate::math::*;
+
+/// Chunks further than view radius plus margin are unloaded
+const UNLOAD_MARGIN: i32 = 2;
+/// Default memory budget in bytes
+const DEFAULT_BUDGET: usize = 256 * 1024 * 1024;
+
+/// Memory budget of all worlds
+static BUDGET: AtomicUsize = AtomicUsize::new(DEFAULT_BUDGET);
+
+/// Storage reader: world and position -> stored chunk
+pub type Loader = fn(&str, IVec3) -> Option<StoredChunk>;
+
+static LOADER: RwLock<Option<Loader>> = RwLock::new(None);
+
+/// Evicted chunk data for storage
+#[derive(Debug, Clone)]
+pub struct StoredChunk {
+    pub world: String,
+    pub pos: IVec3,
+    /// Raw chunk buffer
+    pub data: Vec<u8>,
+    pub biomes: Vec<u16>,
+    /// Last done pipeline stage index
+    pub stage: u8,
+}
+
+/// Resident chunks and meshes memory
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryStats {
+    pub chunks: usize,
+    pub meshes: usize,
+    pub chunk_bytes: usize,
+    pub mesh_bytes: usize,
+    /// Chunks in storage
+    pub persisted: usize,
+    pub budget: usize,
+}
+
+impl MemoryStats {
+    pub fn total_bytes(&self) -> usize {
+        self.chunk_bytes + self.mesh_bytes
+    }
+}
+
+/// World residency state
+#[derive(Debug, Default)]
+pub(crate) struct Residency {
+    /// Chunk position -> last used tick
+    used: Mutex<HashMap<IVec3, u64>>,
+    /// Chunks in storage
+    persisted: Mutex<HashSet<IVec3>>,
+
+    /// Evicted chunks waiting for storage
+    outbox: Mutex<Vec<StoredChunk>>,
+    /// Chunks requested from storage
+    loads: Mutex<HashSet<IVec3>>,
+    /// Resident chunks changed since they were stored (generated too)
+    dirty: Mutex<HashSet<IVec3>>,
+}
+
+/// Set resident chunks and meshes memory budget in bytes
+pub fn set_budget(bytes: usize) {
+    BUDGET.store(bytes, Ordering::Release);
+}
+
+pub fn budget() -> usize {
+    BUDGET.load(Ordering::Acquire)
+}
+
+/// Set storage reader, evicted chunks are loaded back in the same call they are requested
+pub fn set_loader(loader: Loader) {
+    *LOADER.write().unwrap() = Some(loader);
+}
+
+/// Mark chunk as used in this tick
+pub fn touch(pos: IVec3) {
+    let core = crate::core();
+    core.residency.used.lock().unwrap().insert(pos, crate::timers::current_tick());
+}
+
+/// Positions of world chunks already in storage (on startup)
+pub fn add_persisted(world: &str, positions: impl IntoIterator<Item = IVec3>) {
+    let core = crate::create_world(world);
+    core.residency.persisted.lock().unwrap().extend(positions);
+}
+
+pub fn is_persisted(pos: IVec3) -> bool {
+    let core = crate::core();
+    core.residency.persisted.lock().unwrap().contains(&pos)
+}
+
+/// Request chunk from storage instead of generation
+pub fn request_load(pos: IVec3) {
+    let core = crate::core();
+    core.residency.loads.lock().unwrap().insert(pos);
+}
+
+/// Load evicted chunk back now: from the outbox if it's not stored yet or by the loader.
+/// Without loader it's requested and loaded in next ticks, returns true if chunk is resident
+pub fn load(pos: IVec3) -> bool {
+    let core = crate::core();
+    if crate::has_chunk(pos) { return true; }
+    if !is_persisted(pos) { return false; }
+
+    // Evicted in this tick: outbox version is the newest one
+    let pending = {
+        let mut outbox = core.residency.outbox.lock().unwrap();
+        outbox.iter().position(|s| s.pos == pos).map(|i| outbox.swap_remove(i))
+    };
+
+    if let Some(stored) = pending {
+        restore_in(&core, stored);
+        mark_dirty(&core, pos);
+        return crate::has_chunk(pos);
+    }
+
+    let Some(loader) = *LOADER.read().unwrap() else {
+        request_load(pos);
+        return false;
+    };
+
+    match loader(core.id(), pos) {
+        Some(stored) => restore_in(&core, stored),
+        None => { core.residency.persisted.lock().unwrap().remove(&pos); }
+    }
+
+    crate::has_chunk(pos)
+}
+
+/// Take chunks requested from storage of all worlds: world and position
+pub fn take_loads() -> Vec<(String, IVec3)> {
+    let mut result = Vec::new();
+
+    for world in crate::worlds() {
+        let mut loads = std::mem::take(&mut *world.residency.loads.lock().unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        loads.sort_by_key(|p| (p.y, p.z, p.x));
+
+        result.extend(loads.into_iter().map(|p| (world.id().to_string(), p)));
+    }
+
+    result
+}
+
+/// Mark chunk as changed, it's stored with it's biomes while resident
+pub(crate) fn mark_dirty(core: &crate::Core, pos: IVec3) {
+    core.residency.dirty.lock().unwrap().insert(pos);
+}
+
+/// Take copies of changed resident chunks of all worlds for storage
+pub fn take_dirty() -> Vec<StoredChunk> {
+    let mut result = Vec::new();
+
+    for world in crate::worlds() {
+        let mut dirty = std::mem::take(&mut *world.residency.dirty.lock().unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        dirty.sort_by_key(|p| (p.y, p.z, p.x));
+
+        crate::with_world(&world, || {
+            result.extend(dirty.into_iter().filter_map(crate::snapshot_chunk));
+        });
+    }
+
+    result
+}
+
+/// Take evicted chunks of all worlds for storage
+pub fn take_stored() -> Vec<StoredChunk> {
+    crate::worlds().iter()
+        .flat_map(|world| std::mem::take(&mut *world.residency.outbox.lock().unwrap()))
+        .collect()
+}
+
+/// Put chunk from storage back into it's world
+pub fn restore(stored: StoredChunk) {
+    let world = crate::create_world(&stored.world);
+    crate::with_world(&world, || restore_in(&world, stored));
+}
+
+fn restore_in(core: &crate::Core, stored: StoredChunk) {
+    let pos = stored.pos;
+
+    if !crate::restore_chunk(stored) {
+        log::error!("Stored chunk {} of {} is corrupted, it's generated again", pos, core.id());
+        core.residency.persisted.lock().unwrap().remove(&pos);
+        return;
+    }
+
+    touch(pos);
+}
+
+/// Storage has no chunk, it's generated again
+pub fn missing(world: &str, pos: IVec3) {
+    let Some(world) = crate::get_world(world) else { return };
+    world.residency.persisted.lock().unwrap().remove(&pos);
+}
+
+/// Drop resident chunk without storing, newer stored version is loaded back
+pub fn discard(pos: IVec3) {
+    let core = crate::core();
+    if crate::remove_chunk(pos).is_none() { return; }
+
+    core.residency.used.lock().unwrap().remove(&pos);
+    core.residency.dirty.lock().unwrap().remove(&pos);
+    core.residency.persisted.lock().unwrap().insert(pos);
+}
+
+/// Chunk has queued or taken work: it's pinned by script or in the pipeline
+fn is_pinned(core: &crate::Core, pos: IVec3) -> bool {
+    core.gen_queue.lock().unwrap().is_pinned(pos)
+        || core.meshes_queue.lock().unwrap().is_pinned(pos)
+        || core.meshes_queue.lock().unwrap().is_taken(pos)
+        || crate::pipeline::in_work(pos)
+}
+
+/// Remove chunk from the world Core and put it into outbox, returns false if it's pinned
+fn evict(pos: IVec3) -> bool {
+    let core = crate::core();
+    if is_pinned(&core, pos) { return false; }
+
+    let Some(stored) = crate::remove_chunk(pos) else { return false };
+
+    core.residency.used.lock().unwrap().remove(&pos);
+    core.residency.dirty.lock().unwrap().remove(&pos);
+    core.residency.persisted.lock().unwrap().insert(pos);
+    core.residency.outbox.lock().unwrap().push(stored);
+
+    true
+}
+
+/// Memory of all worlds
+pub fn memory_stats() -> MemoryStats {
+    let mut stats = MemoryStats { budget: budget(), ..Default::default() };
+
+    for world in crate::worlds() {
+        let (chunks, meshes, mesh_bytes) = crate::with_world(&world, crate::core_sizes);
+
+        stats.chunks += chunks;
+        stats.meshes += meshes;
+        stats.chunk_bytes += chunks * BUF_SIZE;
+        stats.mesh_bytes += mesh_bytes;
+        stats.persisted += world.residency.persisted.lock().unwrap().len();
+    }
+
+    stats
+}
+
+/// Unload world chunks out of centres range
+pub fn update(centres: &[crate::streaming::Centre]) {
+    let in_range = |pos: IVec3| centres.iter().any(|c| {
+        let r = c.radius + UNLOAD_MARGIN;
+        (pos - c.pos).length_squared() <= r * r
+    });
+
+    for pos in crate::chunk_positions().into_iter().filter(|p| !in_range(*p)) {
+        let _ = evict(pos);
+    }
+}
+
+/// Evict least recently used chunks of all worlds over budget
+pub fn enforce_budget() {
+    let budget = budget();
+    let stats = memory_stats();
+    if stats.total_bytes() <= budget { return; }
+
+    // Least recently used first, never used are the oldest
+    let mut resident = Vec::new();
+    for world in crate::worlds() {
+        let positions = crate::with_world(&world, crate::chunk_positions);
+        let used = world.residency.used.lock().unwrap();
+
+        for pos in positions {
+            resident.push((used.get(&pos).copied().unwrap_or(0), world.id().to_string(), pos));
+        }
+    }
+    resident.sort_by(|a, b| (a.0, &a.1, a.2.to_array()).cmp(&(b.0, &b.1, b.2.to_array())));
+
+    // Average chunk with mesh size
+    let average = stats.total_bytes() / stats.chunks.max(1);
+    let mut total = stats.total_bytes();
+
+    for (_, world, pos) in resident {
+        if total <= budget { break; }
+        let Some(world) = crate::get_world(&world) else { continue };
+
+        if crate::with_world(&world, || evict(pos)) {
+            total = total.saturating_sub(average);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Script roles: expected entry points and scheduling.
+
+use rune::runtime::debug::DebugArgs;
+use crate::pipeline::Stage;
+
+/// What script does in the engine
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    /// Generates chunks from the generator queue
+    #[rune(constructor)]
+    Generator,
+    /// Builds meshes from the mesher queue
+    #[rune(constructor)]
+    Mesher,
+    /// Registers blocks in `init`, has no entry point
+    #[rune(constructor)]
+    BlockRegistry,
+    /// Any other ticked script
+    #[default]
+    #[rune(constructor)]
+    System,
+    /// Called by command with arguments array
+    #[rune(constructor)]
+    Command,
+    /// Does generation pipeline stage from the stage queue
+    #[rune(constructor)]
+    Stage,
+}
+
+impl crate::api::ApiEnum for Role {
+    const VARIANTS: &'static [Self] = &[
+        Self::Generator, Self::Mesher, Self::BlockRegistry, Self::System, Self::Command, Self::Stage,
+    ];
+}
+
+impl Role {
+    /// Expected entry point arguments count, `None` if role has no entry point
+    pub fn entry_args(&self) -> Option<usize> {
+        match self {
+            Self::Generator | Self::Mesher | Self::System | Self::Stage => Some(0),
+            Self::Command => Some(1),
+            Self::BlockRegistry => None,
+        }
+    }
+
+    /// Is entry point called every tick
+    pub fn is_ticked(&self) -> bool {
+        matches!(self, Self::Generator | Self::Mesher | Self::System | Self::Stage)
+    }
+
+    /// Only one script of this role can be active
+    pub fn is_exclusive(&self) -> bool {
+        matches!(self, Self::Generator | Self::Mesher)
+    }
+}
+
+/// Check script entry point by role, returns diagnostic message
+pub(crate) fn validate(unit: &rune::Unit, role: Role, entry: Option<&str>) -> Result<(), String> {
+    let (expected, entry) = match (role.entry_args(), entry) {
+        (None, None) => return Ok(()),
+        (None, Some(entry)) => {
+            return Err(format!("role has no entry point, but `{}` is set", entry));
+        },
+        (Some(_), None) => {
+            return Err(String::from("no entry point: return meta(entry, threading) from init"));
+        },
+        (Some(expected), Some(entry)) => (expected, entry)
+    };
+
+    // Signatures are known from the unit debug info only
+    let Some(debug) = unit.debug_info() else { return Ok(()) };
+
+    let args = match debug.functions.get(&rune::Hash::type_hash([entry])).map(|s| &s.args) {
+        None => return Err(format!("entry function `{}` not found", entry)),
+        Some(DebugArgs::EmptyArgs) => 0,
+        Some(DebugArgs::TupleArgs(args)) => *args,
+        Some(DebugArgs::Named(names)) => names.len(),
+    };
+
+    match args == expected {
+        true => Ok(()),
+        false => Err(format!("entry function `{}` takes {} arguments, expected {}", entry, args, expected))
+    }
+}
+
+/// Check stage of the stage script, terrain is done by generator
+pub(crate) fn validate_stage(role: Role, stage: Option<Stage>) -> Result<(), String> {
+    match (role, stage) {
+        (Role::Stage, None) => Err(String::from("no stage: return meta(entry, threading).stage(stage) from init")),
+        (Role::Stage, Some(Stage::Terrain)) => Err(String::from("terrain stage is done by generator role")),
+        _ => Ok(())
+    }
+}
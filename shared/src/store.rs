@@ -0,0 +1,140 @@
+//! Persistent key-value storage for scripts.
+//! Each script has own namespace (asset path); values are stored as JSON.
+
+use std::{sync::*, collections::*};
+
+use rune::runtime::VmResult;
+
+static STORE: OnceLock<Store> = OnceLock::new();
+
+/// Storage change that must be persisted by backend (server table)
+#[derive(Debug, Clone)]
+pub enum StoreChange {
+    Set { script: String, key: String, value: String },
+    Remove { script: String, key: String },
+}
+
+#[derive(Debug)]
+struct Store {
+    /// Script path -> key -> serialized value
+    values: RwLock<HashMap<String, HashMap<String, String>>>,
+
+    /// Not persisted changes
+    changes: Mutex<Vec<StoreChange>>,
+}
+
+pub(crate) fn init_store() {
+    let values = RwLock::new(HashMap::new());
+    let changes = Mutex::new(Vec::new());
+
+    if STORE.set(Store { values, changes }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Load persisted values: (script, key, value)
+pub fn load_store(entries: impl IntoIterator<Item = (String, String, String)>) {
+    let store = STORE.get().unwrap();
+    let mut guard = store.values.write().unwrap();
+
+    for (script, key, value) in entries {
+        guard.entry(script).or_default().insert(key, value);
+    }
+}
+
+/// Take all changes since last call
+pub fn take_changes() -> Vec<StoreChange> {
+    let store = STORE.get().unwrap();
+    let mut guard = store.changes.lock().unwrap();
+
+    std::mem::take(&mut *guard)
+}
+
+/// Get serialized value manually
+pub fn get_raw(script: &str, key: &str) -> Option<String> {
+    let store = STORE.get().unwrap();
+    let guard = store.values.read().unwrap();
+
+    guard.get(script)?.get(key).cloned()
+}
+
+/// Set serialized value manually
+pub fn set_raw(script: String, key: String, value: String) {
+    let store = STORE.get().unwrap();
+
+    let mut guard = store.values.write().unwrap();
+    guard.entry(script.clone()).or_default().insert(key.clone(), value.clone());
+
+    let mut changes = store.changes.lock().unwrap();
+    changes.push(StoreChange::Set { script, key, value });
+}
+
+/// Remove value manually
+pub fn remove_raw(script: String, key: String) -> bool {
+    let store = STORE.get().unwrap();
+
+    let mut guard = store.values.write().unwrap();
+    let Some(values) = guard.get_mut(&script) else { return false };
+    if values.remove(&key).is_none() { return false; }
+
+    let mut changes = store.changes.lock().unwrap();
+    changes.push(StoreChange::Remove { script, key });
+
+    true
+}
+
+/// Namespace of the calling script
+fn namespace() -> VmResult<String> {
+    match crate::current_script() {
+        Some(script) => VmResult::Ok(script),
+        None => VmResult::panic("Storage is available only in scripts")
+    }
+}
+
+#[rune::function]
+/// Get value by key from script storage
+pub fn store_get(key: String) -> VmResult<Option<rune::Value>> {
+    let Some(raw) = get_raw(&rune::vm_try!(namespace()), &key) else { return VmResult::Ok(None) };
+
+    match serde_json::from_str(&raw) {
+        Ok(value) => VmResult::Ok(Some(value)),
+        Err(e) => VmResult::panic(format!("Storage value error: {}", e))
+    }
+}
+
+#[rune::function]
+/// Set value by key in script storage
+pub fn store_set(key: String, value: rune::Value) -> VmResult<()> {
+    let script = rune::vm_try!(namespace());
+
+    match serde_json::to_string(&value) {
+        Ok(raw) => {
+            set_raw(script, key, raw);
+            VmResult::Ok(())
+        },
+        Err(e) => VmResult::panic(format!("Storage value error: {}", e))
+    }
+}
+
+#[rune::function]
+/// Remove value from script storage, returns true if it existed
+pub fn store_remove(key: String) -> VmResult<bool> {
+    VmResult::Ok(remove_raw(rune::vm_try!(namespace()), key))
+}
+
+#[rune::function]
+/// Get all keys of script storage
+pub fn store_keys() -> VmResult<Vec<String>> {
+    let script = rune::vm_try!(namespace());
+
+    let store = STORE.get().unwrap();
+    let guard = store.values.read().unwrap();
+
+    let mut keys = guard.get(&script)
+        .map(|values| values.keys().cloned().collect::<Vec<String>>())
+        .unwrap_or_default();
+
+    // Stable order
+    keys.sort();
+    VmResult::Ok(keys)
+}
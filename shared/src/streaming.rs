@@ -0,0 +1,143 @@
+//! Chunks streaming of the world: load centres (players, spawn) with view radius.
+//! Missing chunks in radius are queued for generation, generated chunks are
+//! queued for meshing once they and their neighbours passed the pipeline. Queued positions out of
+//! view are cancelled.
+
+use std::{sync::*, collections::*};
+use crate::chunk::*;
+use crate::math::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Centre {
+    /// Chunk position
+    pub pos: IVec3,
+    /// View radius in chunks
+    pub radius: i32,
+}
+
+/// World streaming state
+#[derive(Debug, Default)]
+pub(crate) struct Streaming {
+    /// Centre id (player identity or anchor name) -> centre
+    centres: Mutex<HashMap<String, Centre>>,
+    /// Centres with the world view distance radius (players and spawn)
+    viewers: Mutex<HashSet<String>>,
+}
+
+/// Insert or move load centre
+pub fn set_centre(id: &str, pos: IVec3, radius: i32) {
+    let core = crate::core();
+    let streaming = &core.streaming;
+    let centre = Centre { pos, radius: radius.max(0) };
+
+    streaming.centres.lock().unwrap().insert(id.to_string(), centre);
+}
+
+/// Insert or move load centre with the world view distance radius
+pub(crate) fn set_viewer(id: &str, pos: IVec3) {
+    let core = crate::core();
+    let radius = core.config.read().unwrap().view_distance;
+
+    set_centre(id, pos, radius);
+    core.streaming.viewers.lock().unwrap().insert(id.to_string());
+}
+
+/// Insert or move load centre by world position, radius follows the world view distance
+pub fn set_centre_world(id: &str, pos: Vec3) {
+    let pos = pos.floor().as_ivec3().div_euclid(IVec3::splat(SIZE_I32));
+    set_viewer(id, pos);
+}
+
+/// Change radius of centres following the world view distance
+pub(crate) fn set_view_distance(radius: i32) {
+    let core = crate::core();
+    let streaming = &core.streaming;
+    let viewers = streaming.viewers.lock().unwrap();
+
+    for (id, centre) in streaming.centres.lock().unwrap().iter_mut() {
+        if viewers.contains(id) { centre.radius = radius.max(0); }
+    }
+}
+
+pub fn remove_centre(id: &str) {
+    let core = crate::core();
+    let streaming = &core.streaming;
+    streaming.centres.lock().unwrap().remove(id);
+    streaming.viewers.lock().unwrap().remove(id);
+}
+
+pub fn centres() -> Vec<(String, Centre)> {
+    let core = crate::core();
+    let streaming = &core.streaming;
+    let guard = streaming.centres.lock().unwrap();
+
+    let mut result = guard.iter().map(|(id, c)| (id.clone(), *c)).collect::<Vec<_>>();
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+
+    result
+}
+
+/// Chunk positions of all centres
+pub fn centre_positions() -> Vec<IVec3> {
+    centres().into_iter().map(|(_, c)| c.pos).collect()
+}
+
+/// Chunk positions in the view radius (sphere)
+fn positions(centre: Centre) -> impl Iterator<Item = IVec3> {
+    let r = centre.radius;
+    (-r..=r).flat_map(move |y| (-r..=r).flat_map(move |z| (-r..=r).map(move |x| IVec3::new(x, y, z))))
+        .filter(move |o| o.length_squared() <= r * r)
+        .map(move |o| centre.pos + o)
+}
+
+/// Queue missing chunks of all centres for generation and ready ones for meshing
+pub fn update() {
+    let centres = centres().into_iter().map(|(_, c)| c).collect::<Vec<_>>();
+
+    let mut visible = HashSet::new();
+    for centre in &centres {
+        visible.extend(positions(*centre));
+    }
+
+    crate::cancel_queued(|pos| visible.contains(&pos));
+    crate::residency::update(&centres);
+
+    // Sorted for stable queues order
+    let mut visible = visible.into_iter().collect::<Vec<_>>();
+    visible.sort_by_key(|p| (p.y, p.z, p.x));
+
+    // Queues skip positions already queued or in work
+    for pos in visible {
+        if !crate::has_chunk(pos) {
+            // Evicted chunks are loaded back instead of generation
+            match crate::residency::is_persisted(pos) {
+                true => crate::residency::request_load(pos),
+                false => crate::push_gen(pos)
+            }
+
+            continue;
+        }
+
+        crate::residency::touch(pos);
+
+        if !crate::has_mesh(pos) && crate::pipeline::is_complete(pos) {
+            crate::push_mesh(pos);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------------------------
+// Streaming functions
+
+#[rune::function]
+/// Add fixed load centre (chunk position) or move it
+pub fn add_anchor(name: String, pos: &RnIVec3, radius: i32) {
+    let core = crate::core();
+    core.streaming.viewers.lock().unwrap().remove(&name);
+    set_centre(&name, pos.0, radius)
+}
+
+#[rune::function]
+pub fn remove_anchor(name: String) {
+    remove_centre(&name)
+}
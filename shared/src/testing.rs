@@ -0,0 +1,210 @@
+//! Scripts test harness: runs Rune `#[test]` functions against headless Core.
+
+use std::{path::*, sync::*};
+use rune::compile::{meta, CompileVisitor, MetaError, MetaRef};
+use crate::math::*;
+use crate::chunk::*;
+use crate::mesh::Mesh;
+
+/// Collects test functions of the compiled unit
+#[derive(Default)]
+struct TestVisitor {
+    tests: Vec<(rune::Hash, String)>,
+}
+
+impl CompileVisitor for TestVisitor {
+    fn register_meta(&mut self, meta: MetaRef<'_>) -> Result<(), MetaError> {
+        if let meta::Kind::Function { is_test: true, .. } = meta.kind {
+            self.tests.push((meta.hash, meta.item.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Scripts tests results
+#[derive(Debug, Default)]
+pub struct TestReport {
+    pub passed: Vec<String>,
+    /// Test name and error
+    pub failed: Vec<(String, String)>,
+}
+
+impl TestReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl std::fmt::Display for TestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for name in &self.passed {
+            writeln!(f, "test {} ... ok", name)?;
+        }
+
+        for (name, error) in &self.failed {
+            writeln!(f, "test {} ... FAILED\n  {}", name, error)?;
+        }
+
+        write!(f, "{} passed; {} failed", self.passed.len(), self.failed.len())
+    }
+}
+
+/// Init headless Core if it's not initialized yet
+pub fn setup() {
+    if !crate::is_initalized() {
+        crate::init();
+    }
+}
+
+/// Replace world with an empty one that has the same config.
+/// Used to generate the same world again
+pub fn reset_world(id: &str) {
+    setup();
+
+    let config = crate::create_world(id).config.read().unwrap().clone();
+    let core = Arc::new(crate::Core::new(id));
+    crate::WORLDS.get().unwrap().write().unwrap().insert(id.to_string(), core.clone());
+
+    crate::with_world(&core, || crate::world::apply(config)).expect("World config error");
+    crate::worldgen::clear_pending(id);
+
+    // Events of the previous world
+    crate::events::take_events();
+}
+
+/// Load fixture world from region files directory, returns chunks count
+pub fn load_world(dir: impl AsRef<Path>, world: &str) -> Result<usize, String> {
+    setup();
+
+    let chunks = crate::region_file::read_world(dir, world)?;
+    let count = chunks.len();
+
+    for chunk in chunks {
+        crate::residency::restore(chunk);
+    }
+
+    Ok(count)
+}
+
+/// Insert chunk filled with one block
+pub fn fill_chunk(pos: IVec3, block: u16) -> Chunk {
+    let chunk = Chunk::empty();
+
+    {
+        let mut raw = chunk.write();
+        for i in 0..SIZE_P3 { raw.set_block(i, block); }
+    }
+
+    crate::insert_chunk(pos, chunk.clone());
+    chunk
+}
+
+/// Get raw chunk data, used to compare generated worlds
+pub fn chunk_bytes(pos: IVec3) -> Option<Vec<u8>> {
+    crate::_get_chunk(pos).map(|chunk| chunk.read().as_bytes().to_vec())
+}
+
+/// Get built mesh by chunk position
+pub fn mesh(pos: IVec3) -> Option<Mesh> {
+    crate::_get_mesh(pos)
+}
+
+/// Call entry point of the loaded script once
+pub fn call_entry(path: &str) -> rune::support::Result<()> {
+    let scripts = crate::SCRIPTS.get().unwrap();
+    let guard = scripts.values.read().unwrap();
+
+    let Some(script) = guard.get(path) else { return Ok(()) };
+    let Some(entry) = script.meta.entry.as_deref() else { return Ok(()) };
+
+    let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+    crate::with_script(path, entry, || vm.call([entry], ()))?;
+
+    Ok(())
+}
+
+/// Call function of the loaded script without arguments, returns it's value
+pub fn call<T: rune::FromValue>(path: &str, function: &str) -> rune::support::Result<T> {
+    let scripts = crate::SCRIPTS.get().unwrap();
+    let guard = scripts.values.read().unwrap();
+
+    let Some(script) = guard.get(path) else {
+        return Err(rune::support::Error::msg(format!("Script {} not found", path)));
+    };
+
+    let mut vm = rune::Vm::new(scripts.runtime.clone(), script.unit.clone());
+    let value = crate::in_world(script.meta.world_id(), || crate::with_script(path, function, || vm.call([function], ())))?;
+
+    Ok(rune::from_value(value)?)
+}
+
+/// Load all directory scripts, returns (path, source).
+/// Paths are relative to the directory
+pub fn load_scripts(dir: impl AsRef<Path>) -> rune::support::Result<Vec<(String, String)>> {
+    let dir = dir.as_ref();
+
+    let mut paths = crate::assets::assets_paths(dir).into_iter()
+        .filter(|p| p.extension().is_some_and(|e| e == "rn"))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let mut result = Vec::with_capacity(paths.len());
+    for path in paths {
+        let source = std::fs::read_to_string(&path)?;
+        let relative = path.strip_prefix(dir)?.to_string_lossy().replace('\\', "/");
+
+        crate::insert_script(relative.clone(), &source)?;
+        result.push((relative, source));
+    }
+
+    Ok(result)
+}
+
+/// Compile script with tests and run all of them
+pub fn run_tests(path: &str, source: &str, report: &mut TestReport) -> rune::support::Result<()> {
+    let scripts = crate::SCRIPTS.get().unwrap();
+
+    let mut sources = rune::Sources::new();
+    sources.insert(rune::Source::new(path, source)?)?;
+
+    let mut options = rune::Options::default();
+    options.test(true);
+
+    let mut visitor = TestVisitor::default();
+    let result = rune::prepare(&mut sources)
+        .with_context(&scripts.context)
+        .with_options(&options)
+        .with_visitor(&mut visitor)?
+        .build();
+
+    let unit = match result {
+        Ok(unit) => Arc::new(unit),
+        Err(e) => {
+            report.failed.push((path.to_string(), format!("Script build error: {}", e)));
+            return Ok(());
+        }
+    };
+
+    for (hash, name) in visitor.tests {
+        let mut vm = rune::Vm::new(scripts.runtime.clone(), unit.clone());
+        match crate::with_script(path, &name, || vm.call(hash, ())) {
+            Ok(_) => report.passed.push(format!("{}::{}", path, name)),
+            Err(e) => report.failed.push((format!("{}::{}", path, name), e.to_string())),
+        }
+    }
+
+    Ok(())
+}
+
+/// Load directory scripts and run all their tests
+pub fn run_dir(dir: impl AsRef<Path>) -> rune::support::Result<TestReport> {
+    setup();
+
+    let mut report = TestReport::default();
+    for (path, source) in load_scripts(dir)? {
+        run_tests(&path, &source, &mut report)?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,158 @@
+//! Delayed and repeated scripts calls.
+//! Calls are stored per script and survive hot reload if function still exists.
+
+use std::{sync::*, sync::atomic::*, collections::*};
+use rune::runtime::VmResult;
+
+static TIMERS: OnceLock<Timers> = OnceLock::new();
+
+/// Pending script function call
+#[derive(Debug, Clone)]
+pub struct Timer {
+    pub handle: u64,
+    pub function: String,
+    /// Serialized (JSON) arguments array
+    pub args: String,
+
+    /// Tick of the next call
+    pub at: u64,
+    /// Repeat period in ticks
+    pub every: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Timers {
+    /// Current tick number
+    tick: AtomicU64,
+    next_handle: AtomicU64,
+
+    /// Script path -> pending calls
+    pending: Mutex<HashMap<String, Vec<Timer>>>,
+}
+
+pub(crate) fn init_timers() {
+    let tick = AtomicU64::new(0);
+    let next_handle = AtomicU64::new(1);
+    let pending = Mutex::new(HashMap::new());
+
+    if TIMERS.set(Timers { tick, next_handle, pending }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Current tick number
+pub fn current_tick() -> u64 {
+    TIMERS.get().unwrap().tick.load(Ordering::Acquire)
+}
+
+/// Advance tick and take all calls that must be done: (script, timer).
+/// Repeated calls are scheduled again
+pub(crate) fn advance() -> Vec<(String, Timer)> {
+    let timers = TIMERS.get().unwrap();
+    let tick = timers.tick.fetch_add(1, Ordering::AcqRel) + 1;
+
+    let mut result = Vec::new();
+    let mut guard = timers.pending.lock().unwrap();
+
+    for (script, pending) in guard.iter_mut() {
+        pending.retain_mut(|timer| {
+            if timer.at > tick { return true; }
+            result.push((script.clone(), timer.clone()));
+
+            match timer.every {
+                Some(every) => { timer.at = tick + every; true },
+                None => false
+            }
+        });
+    }
+
+    // Stable order of calls
+    result.sort_by_key(|(_, timer)| timer.handle);
+    result
+}
+
+/// Add new pending call, returns handle
+pub fn schedule(script: String, function: String, args: String, ticks: u64, every: Option<u64>) -> u64 {
+    let timers = TIMERS.get().unwrap();
+    let handle = timers.next_handle.fetch_add(1, Ordering::AcqRel);
+
+    // Call on the next tick at least
+    let at = current_tick() + ticks.max(1);
+    let timer = Timer { handle, function, args, at, every };
+
+    let mut guard = timers.pending.lock().unwrap();
+    guard.entry(script).or_default().push(timer);
+
+    handle
+}
+
+/// Cancel pending call of the script by handle
+pub fn cancel(script: &str, handle: u64) -> bool {
+    let timers = TIMERS.get().unwrap();
+    let mut guard = timers.pending.lock().unwrap();
+
+    let Some(pending) = guard.get_mut(script) else { return false };
+    let Some(i) = pending.iter().position(|t| t.handle == handle) else { return false };
+
+    pending.remove(i);
+    true
+}
+
+/// Keep only script calls which function is still exists
+pub(crate) fn retain_script(script: &str, exists: impl Fn(&str) -> bool) {
+    let timers = TIMERS.get().unwrap();
+    let mut guard = timers.pending.lock().unwrap();
+
+    if let Some(pending) = guard.get_mut(script) {
+        pending.retain(|t| exists(&t.function));
+    }
+}
+
+/// Remove all script calls
+pub(crate) fn remove_script(script: &str) {
+    let timers = TIMERS.get().unwrap();
+    timers.pending.lock().unwrap().remove(script);
+}
+
+/// Remove all calls
+pub(crate) fn clear_timers() {
+    let timers = TIMERS.get().unwrap();
+    timers.pending.lock().unwrap().clear();
+}
+
+fn namespace() -> VmResult<String> {
+    match crate::current_script() {
+        Some(script) => VmResult::Ok(script),
+        None => VmResult::panic("Scheduling is available only in scripts")
+    }
+}
+
+#[rune::function]
+/// Call script function once after ticks count with arguments array
+pub fn schedule_after(ticks: u64, function: String, args: rune::Value) -> VmResult<u64> {
+    let script = rune::vm_try!(namespace());
+
+    // Arguments are checked now: failed call would be lost
+    let args = match serde_json::to_value(&args) {
+        Ok(args @ serde_json::Value::Array(_)) => args.to_string(),
+        Ok(_) => return VmResult::panic("Schedule arguments must be an array"),
+        Err(e) => return VmResult::panic(format!("Schedule arguments error: {}", e))
+    };
+
+    VmResult::Ok(schedule(script, function, args, ticks, None))
+}
+
+#[rune::function]
+/// Call script function every ticks count
+pub fn schedule_every(ticks: u64, function: String) -> VmResult<u64> {
+    let script = rune::vm_try!(namespace());
+    let every = ticks.max(1);
+
+    VmResult::Ok(schedule(script, function, String::from("[]"), every, Some(every)))
+}
+
+#[rune::function]
+/// Cancel scheduled call of the calling script, returns false if it's already done
+pub fn cancel_schedule(handle: u64) -> VmResult<bool> {
+    VmResult::Ok(cancel(&rune::vm_try!(namespace()), handle))
+}
@@ -0,0 +1,124 @@
+//! Worlds configuration: seed, generator, view distance, TPS, spawn and flat layers.
+//! Every world has it's own config stored by the server, read-only for scripts.
+
+use crate::chunk::*;
+use crate::math::*;
+use crate::worldgen::FlatConfig;
+
+/// Spawn anchor id in streaming
+pub const SPAWN_ANCHOR: &str = "spawn";
+/// Max total thickness of flat generator layers
+pub const MAX_FLAT_HEIGHT: u32 = 1024;
+
+#[derive(rune::Any, Debug, Clone)]
+pub struct WorldConfig {
+    #[rune(get)]
+    pub seed: u64,
+    /// Generator name: script, flat or noise
+    #[rune(get)]
+    pub generator: String,
+    /// Chunks loading radius around players and spawn
+    #[rune(get)]
+    pub view_distance: i32,
+    /// Ticks per second
+    #[rune(get)]
+    pub tps: u32,
+    /// Spawn world position
+    #[rune(get, copy)]
+    pub spawn: RnIVec3,
+
+    /// World height of the flat generator bottom layer
+    #[rune(get)]
+    pub flat_base: i32,
+    /// Flat generator block names and thickness, from bottom to top
+    pub flat_layers: Vec<(String, u32)>,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1337,
+            generator: String::from("script"),
+            view_distance: 8,
+            tps: 30,
+            spawn: RnIVec3(IVec3::ZERO),
+            flat_base: FlatConfig::default().base,
+            flat_layers: FlatConfig::default().layers,
+        }
+    }
+}
+
+impl WorldConfig {
+    /// Seed for noise generators
+    pub fn noise_seed(&self) -> i32 {
+        self.seed as i32
+    }
+
+    /// Flat generator parameters
+    pub fn flat(&self) -> FlatConfig {
+        FlatConfig { base: self.flat_base, layers: self.flat_layers.clone() }
+    }
+}
+
+/// Config of the current world
+pub fn world_config_raw() -> WorldConfig {
+    crate::core().config.read().unwrap().clone()
+}
+
+/// Seed of the current world
+pub fn seed() -> u64 {
+    crate::core().config.read().unwrap().seed
+}
+
+/// Replace config of the current world and apply it to the engine
+pub fn apply(config: WorldConfig) -> Result<(), String> {
+    if crate::worldgen::GeneratorKind::parse(&config.generator).is_none() {
+        return Err(format!("Unknown generator {}", config.generator));
+    }
+
+    if config.tps == 0 {
+        return Err(String::from("TPS must be positive"));
+    }
+
+    let height = config.flat_layers.iter().try_fold(0u32, |sum, (_, thickness)| sum.checked_add(*thickness));
+    if height.is_none_or(|h| h > MAX_FLAT_HEIGHT) {
+        return Err(format!("Flat layers must be at most {} blocks thick", MAX_FLAT_HEIGHT));
+    }
+
+    // Scripts random numbers follow the default world seed, deterministic mode has it's own.
+    // Generators are restarted only by a new seed
+    let core = crate::core();
+    if core.id() == crate::DEFAULT_WORLD && !crate::is_deterministic() && crate::random::seed() != config.seed {
+        crate::random::set_seed(config.seed);
+    }
+
+    let (spawn, radius) = (config.spawn.0.div_euclid(IVec3::splat(SIZE_I32)), config.view_distance);
+    *core.config.write().unwrap() = config;
+
+    // Players centres and spawn follow the view distance
+    crate::streaming::set_viewer(SPAWN_ANCHOR, spawn);
+    crate::streaming::set_view_distance(radius);
+
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------------------------
+// World functions
+
+#[rune::function]
+/// Config of the script's world (read-only)
+pub fn world_config() -> WorldConfig {
+    world_config_raw()
+}
+
+#[rune::function(instance)]
+/// Seed for noise generators
+pub fn noise_seed(config: &WorldConfig) -> i32 {
+    config.noise_seed()
+}
+
+#[rune::function(instance)]
+/// Flat generator layers: block name and thickness, from bottom to top
+pub fn flat_layers(config: &WorldConfig) -> Vec<(String, u32)> {
+    config.flat_layers.clone()
+}
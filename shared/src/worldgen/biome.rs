@@ -0,0 +1,209 @@
+//! Biomes registry and per-column biome sampler.
+//! Biome is selected by temperature and humidity noise (both in 0..1 range).
+
+use std::{sync::*, collections::*};
+use crate::chunk::*;
+use crate::math::*;
+use super::{Noise, NoiseType, FractalType};
+
+/// Column without biome
+pub const NO_BIOME: u16 = u16::MAX;
+
+/// Climate noise frequency
+const FREQUENCY: f32 = 0.002;
+
+static BIOMES: OnceLock<BiomesHandler> = OnceLock::new();
+
+#[derive(Debug, rune::Any, Clone)]
+pub struct Biome {
+    #[rune(get, set)]
+    pub temperature_min: f64,
+    #[rune(get, set)]
+    pub temperature_max: f64,
+    #[rune(get, set)]
+    pub humidity_min: f64,
+    #[rune(get, set)]
+    pub humidity_max: f64,
+
+    /// Top block name
+    #[rune(get, set)]
+    pub surface: String,
+    /// Block name under the surface
+    #[rune(get, set)]
+    pub filler: String,
+    /// Structures names
+    #[rune(set)]
+    pub decorations: Vec<String>,
+}
+
+impl Biome {
+    fn contains(&self, temperature: f64, humidity: f64) -> bool {
+        (self.temperature_min..=self.temperature_max).contains(&temperature)
+            && (self.humidity_min..=self.humidity_max).contains(&humidity)
+    }
+
+    /// Squared distance to the climate range center
+    fn distance(&self, temperature: f64, humidity: f64) -> f64 {
+        let t = (self.temperature_min + self.temperature_max) / 2.0 - temperature;
+        let h = (self.humidity_min + self.humidity_max) / 2.0 - humidity;
+
+        t * t + h * h
+    }
+}
+
+#[derive(Debug)]
+struct BiomesHandler {
+    biomes: RwLock<Vec<Biome>>,
+    names: RwLock<HashMap<String, u16>>,
+}
+
+pub(crate) fn init_biomes() {
+    let biomes = RwLock::new(Vec::new());
+    let names = RwLock::new(HashMap::new());
+
+    if BIOMES.set(BiomesHandler { biomes, names }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Climate noises: temperature and humidity
+fn climate() -> (Noise, Noise) {
+    let seed = crate::world::seed() as i32;
+
+    let mut temperature = Noise::new(seed, NoiseType::OpenSimplex2, FREQUENCY);
+    temperature.set_fractal(FractalType::FBm, 3, 0.5);
+
+    let mut humidity = Noise::new(seed.wrapping_add(1), NoiseType::OpenSimplex2, FREQUENCY);
+    humidity.set_fractal(FractalType::FBm, 3, 0.5);
+
+    (temperature, humidity)
+}
+
+/// Select biome by climate from registered biomes
+fn select(biomes: &[Biome], temperature: f64, humidity: f64) -> u16 {
+    if let Some(i) = biomes.iter().position(|b| b.contains(temperature, humidity)) {
+        return i as u16;
+    }
+
+    // Nearest by climate
+    biomes.iter().enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.distance(temperature, humidity).total_cmp(&b.distance(temperature, humidity))
+        })
+        .map(|(i, _)| i as u16)
+        .unwrap_or(NO_BIOME)
+}
+
+/// Biome id of the world column
+pub fn sample_biome(x: i32, z: i32) -> u16 {
+    let handler = BIOMES.get().unwrap();
+    let biomes = handler.biomes.read().unwrap();
+    let (temperature, humidity) = climate();
+
+    let t = (temperature.sample2(x as f32, z as f32) as f64 + 1.0) / 2.0;
+    let h = (humidity.sample2(x as f32, z as f32) as f64 + 1.0) / 2.0;
+
+    select(&biomes, t, h)
+}
+
+/// Biome ids of all chunk columns (index: x + z * SIZE)
+pub fn biome_map(pos: IVec3) -> Vec<u16> {
+    let handler = BIOMES.get().unwrap();
+    let biomes = handler.biomes.read().unwrap();
+    let (temperature, humidity) = climate();
+
+    let origin = pos * SIZE_I32;
+    let mut result = Vec::with_capacity(SIZE * SIZE);
+
+    for z in 0..SIZE_I32 {
+        for x in 0..SIZE_I32 {
+            let (wx, wz) = ((origin.x + x) as f32, (origin.z + z) as f32);
+            let t = (temperature.sample2(wx, wz) as f64 + 1.0) / 2.0;
+            let h = (humidity.sample2(wx, wz) as f64 + 1.0) / 2.0;
+
+            result.push(select(&biomes, t, h));
+        }
+    }
+
+    result
+}
+
+pub fn get_biome(id: u16) -> Option<Biome> {
+    let handler = BIOMES.get().unwrap();
+    handler.biomes.read().unwrap().get(id as usize).cloned()
+}
+
+pub fn get_biome_name(id: u16) -> Option<String> {
+    let handler = BIOMES.get().unwrap();
+    let guard = handler.names.read().unwrap();
+
+    guard.iter().find(|(_, i)| **i == id).map(|(name, _)| name.clone())
+}
+
+// ----------------------------------------------------------------------------------------------
+// Biomes functions
+
+#[rune::function]
+/// Create biome with full climate range
+pub fn new_biome(surface: String, filler: String) -> Biome {
+    Biome {
+        temperature_min: 0.0,
+        temperature_max: 1.0,
+        humidity_min: 0.0,
+        humidity_max: 1.0,
+        surface,
+        filler,
+        decorations: Vec::new(),
+    }
+}
+
+#[rune::function(instance, path = decorations)]
+/// Copy of biome structures names
+pub fn biome_decorations(biome: &Biome) -> Vec<String> {
+    biome.decorations.clone()
+}
+
+#[rune::function]
+pub fn clear_biomes() {
+    let handler = BIOMES.get().unwrap();
+    handler.names.write().unwrap().clear();
+    handler.biomes.write().unwrap().clear();
+}
+
+#[rune::function]
+/// Add biome to registry or replace it
+pub fn add_biome(name: String, biome: Biome) {
+    let handler = BIOMES.get().unwrap();
+
+    let mut names = handler.names.write().unwrap();
+    let mut biomes = handler.biomes.write().unwrap();
+
+    if let Some(id) = names.get(&name) {
+        biomes[*id as usize] = biome;
+        return;
+    }
+
+    names.insert(name, biomes.len() as u16);
+    biomes.push(biome);
+}
+
+#[rune::function]
+/// Biome data by name
+pub fn biome(name: String) -> Option<Biome> {
+    let handler = BIOMES.get().unwrap();
+    let id = *handler.names.read().unwrap().get(&name)?;
+
+    get_biome(id)
+}
+
+#[rune::function]
+/// Biome name by id
+pub fn biome_name(id: u16) -> Option<String> {
+    get_biome_name(id)
+}
+
+#[rune::function]
+/// Biome name of the world column
+pub fn biome_at(x: i32, z: i32) -> Option<String> {
+    get_biome_name(sample_biome(x, z))
+}
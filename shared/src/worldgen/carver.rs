@@ -0,0 +1,241 @@
+//! Cave carvers: 3D noise caves (cheese and spaghetti) and worm tunnels.
+//! Worm is seeded by it's start chunk and every chunk carves own part of the
+//! neighbours worms, so tunnels match on borders. Only replaceable blocks are carved.
+//! Config is loaded from `.carving` asset (JSON).
+
+use std::sync::*;
+use crate::chunk::*;
+use crate::math::*;
+use super::{Noise, NoiseType, FractalType};
+
+/// Carved blocks become air
+const AIR: u16 = 0;
+
+static CARVING: OnceLock<RwLock<CarvingConfig>> = OnceLock::new();
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct NoiseCaves {
+    pub enabled: bool,
+    pub frequency: f32,
+    /// Cheese caves where noise is above threshold
+    pub cheese: f32,
+    /// Spaghetti tunnels where two noises are near zero
+    pub spaghetti: f32,
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl Default for NoiseCaves {
+    fn default() -> Self {
+        Self { enabled: true, frequency: 0.03, cheese: 0.6, spaghetti: 0.06, min_y: -128, max_y: 16 }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct WormCaves {
+    pub enabled: bool,
+    /// Chance of worm start in a chunk
+    pub chance: f64,
+    /// Steps count
+    pub length: u32,
+    pub step: f32,
+    pub radius_min: f32,
+    pub radius_max: f32,
+    /// Start height range
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl Default for WormCaves {
+    fn default() -> Self {
+        Self {
+            enabled: true, chance: 0.15, length: 48, step: 1.0,
+            radius_min: 1.5, radius_max: 3.0, min_y: -96, max_y: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct CarvingConfig {
+    pub noise: NoiseCaves,
+    pub worms: WormCaves,
+}
+
+pub(crate) fn init_carving() {
+    if CARVING.set(RwLock::new(CarvingConfig::default())).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Load carving config asset
+pub fn set_carving(value: &[u8]) -> Result<(), String> {
+    let config: CarvingConfig = serde_json::from_slice(value).map_err(|e| e.to_string())?;
+    *CARVING.get().unwrap().write().unwrap() = config;
+
+    Ok(())
+}
+
+/// Back to default config
+pub fn reset_carving() {
+    *CARVING.get().unwrap().write().unwrap() = CarvingConfig::default();
+}
+
+pub fn carving() -> CarvingConfig {
+    CARVING.get().unwrap().read().unwrap().clone()
+}
+
+/// Carve block if it's replaceable, returns true if carved
+fn carve_block(raw: &mut RawChunk, mask: &[bool], index: usize) -> bool {
+    let id = raw.get_block(index);
+    if id == AIR || !mask.get(id as usize).copied().unwrap_or(false) { return false; }
+
+    raw.set_block(index, AIR);
+    true
+}
+
+/// Carve noise caves in the chunk, returns carved blocks count
+pub fn carve_noise(pos: IVec3, chunk: &Chunk, config: &NoiseCaves) -> usize {
+    let origin = pos * SIZE_I32;
+    if !config.enabled || origin.y > config.max_y || origin.y + SIZE_I32 <= config.min_y { return 0; }
+
+    let seed = crate::world::seed() as i32;
+    let mut cheese = Noise::new(seed.wrapping_add(10), NoiseType::OpenSimplex2, config.frequency);
+    cheese.set_fractal(FractalType::FBm, 2, 0.5);
+
+    let a = Noise::new(seed.wrapping_add(11), NoiseType::OpenSimplex2, config.frequency);
+    let b = Noise::new(seed.wrapping_add(12), NoiseType::OpenSimplex2, config.frequency);
+
+    let mask = replaceable_mask();
+    let mut raw = chunk.write();
+    let mut count = 0;
+
+    for y in 0..SIZE_I32 {
+        let wy = origin.y + y;
+        if wy < config.min_y || wy > config.max_y { continue; }
+
+        for z in 0..SIZE_I32 {
+            for x in 0..SIZE_I32 {
+                let (wx, wz) = ((origin.x + x) as f32, (origin.z + z) as f32);
+
+                let is_cheese = cheese.sample3(wx, wy as f32, wz) > config.cheese;
+                let is_spaghetti = a.sample3(wx, wy as f32, wz).abs() < config.spaghetti
+                    && b.sample3(wx, wy as f32, wz).abs() < config.spaghetti;
+
+                if !is_cheese && !is_spaghetti { continue; }
+
+                let index = RawChunk::block_index(IVec3::new(x, y, z));
+                count += carve_block(&mut raw, &mask, index) as usize;
+            }
+        }
+    }
+
+    count
+}
+
+/// Worm random generator seeded by start chunk
+struct WormRandom(u64);
+
+impl WormRandom {
+    fn new(seed: u64, pos: IVec3) -> Self {
+        let state = seed
+            ^ (pos.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (pos.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (pos.z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+        Self(state)
+    }
+
+    /// Number in 0..1 range
+    fn next(&mut self) -> f32 {
+        (crate::random::split_mix(&mut self.0) >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Carve parts of all worms reaching the chunk, returns carved blocks count
+pub fn carve_worms(pos: IVec3, chunk: &Chunk, config: &WormCaves) -> usize {
+    if !config.enabled { return 0; }
+
+    // Chunks distance where worm can start
+    let reach = config.length as f32 * config.step + config.radius_max;
+    let range = (reach / SIZE as f32).ceil() as i32;
+
+    let origin = (pos * SIZE_I32).as_vec3();
+    let (min, max) = (origin, origin + Vec3::splat(SIZE as f32));
+
+    let seed = crate::world::seed();
+    let mask = replaceable_mask();
+    let mut raw = chunk.write();
+    let mut count = 0;
+
+    for sy in -range..=range {
+        for sz in -range..=range {
+            for sx in -range..=range {
+                let start = pos + IVec3::new(sx, sy, sz);
+                let mut random = WormRandom::new(seed, start);
+                if random.next() as f64 >= config.chance { continue; }
+
+                let offset = Vec3::new(random.next(), random.next(), random.next()) * SIZE as f32;
+                let mut p = (start * SIZE_I32).as_vec3() + offset;
+                if p.y < config.min_y as f32 || p.y > config.max_y as f32 { continue; }
+
+                let mut yaw = random.next() * std::f32::consts::TAU;
+                let mut pitch = (random.next() - 0.5) * 0.5;
+                let radius = config.radius_min + random.next() * (config.radius_max - config.radius_min);
+
+                for _ in 0..config.length {
+                    let direction = Vec3::new(yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+                    p += direction * config.step;
+
+                    yaw += (random.next() - 0.5) * 0.4;
+                    pitch = (pitch * 0.9 + (random.next() - 0.5) * 0.2).clamp(-0.8, 0.8);
+
+                    // Sphere doesn't touch the chunk
+                    if p.clamp(min, max).distance_squared(p) > radius * radius { continue; }
+
+                    let from = (p - Vec3::splat(radius)).floor().as_ivec3().max(min.as_ivec3());
+                    let to = (p + Vec3::splat(radius)).ceil().as_ivec3().min(max.as_ivec3() - IVec3::ONE);
+
+                    for y in from.y..=to.y {
+                        for z in from.z..=to.z {
+                            for x in from.x..=to.x {
+                                let world = IVec3::new(x, y, z);
+                                if (world.as_vec3() + Vec3::splat(0.5)).distance_squared(p) > radius * radius { continue; }
+
+                                let index = RawChunk::block_index(world - min.as_ivec3());
+                                count += carve_block(&mut raw, &mask, index) as usize;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    count
+}
+
+/// Native carving stage with current config
+pub fn carve_stage(pos: IVec3, chunk: &Chunk) {
+    let config = carving();
+
+    carve_noise(pos, chunk, &config.noise);
+    carve_worms(pos, chunk, &config.worms);
+}
+
+// ----------------------------------------------------------------------------------------------
+// Carving functions
+
+#[rune::function]
+/// Carve noise caves in the chunk with config from asset, returns carved blocks count
+pub fn carve_noise_caves(chunk: &Chunk, pos: &RnIVec3) -> usize {
+    carve_noise(pos.0, chunk, &carving().noise)
+}
+
+#[rune::function]
+/// Carve worm tunnels in the chunk with config from asset, returns carved blocks count
+pub fn carve_worm_caves(chunk: &Chunk, pos: &RnIVec3) -> usize {
+    carve_worms(pos.0, chunk, &carving().worms)
+}
@@ -0,0 +1,199 @@
+//! Native reference generators: superflat layers and noise heightmap terrain.
+//! Generator selected by world config is used when no generator script of the
+//! world is active or when it's forced. Also a performance baseline for scripts.
+
+use std::sync::*;
+use crate::chunk::*;
+use crate::math::*;
+use super::{Noise, NoiseType, FractalType};
+
+static GENERATORS: OnceLock<RwLock<Generators>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeneratorKind {
+    /// Generator script, noise terrain if no script is active
+    #[default]
+    Script,
+    Flat,
+    Noise,
+}
+
+impl GeneratorKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "script" => Some(Self::Script),
+            "flat" => Some(Self::Flat),
+            "noise" => Some(Self::Noise),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Script => "script",
+            Self::Flat => "flat",
+            Self::Noise => "noise",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlatConfig {
+    /// World height of the bottom layer
+    pub base: i32,
+    /// Block names and thickness, from bottom to top
+    pub layers: Vec<(String, u32)>,
+}
+
+impl Default for FlatConfig {
+    fn default() -> Self {
+        let layers = vec![(String::from("Dirt"), 3), (String::from("Grass"), 1)];
+        Self { base: -4, layers }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TerrainConfig {
+    pub frequency: f32,
+    pub octaves: i32,
+    pub base: i32,
+    pub amplitude: f32,
+
+    /// Blocks if column has no biome
+    pub surface: String,
+    pub filler: String,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self {
+            frequency: 0.01, octaves: 4, base: 0, amplitude: 24.0,
+            surface: String::from("Grass"), filler: String::from("Dirt"),
+        }
+    }
+}
+
+/// Native generators parameters shared by all worlds, flat layers are set by world config
+#[derive(Debug, Clone, Default)]
+pub struct Generators {
+    pub terrain: TerrainConfig,
+}
+
+pub(crate) fn init_generators() {
+    if GENERATORS.set(RwLock::new(Generators::default())).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+pub fn generators() -> Generators {
+    GENERATORS.get().unwrap().read().unwrap().clone()
+}
+
+pub fn set_generators(value: Generators) {
+    *GENERATORS.get().unwrap().write().unwrap() = value;
+}
+
+/// Generator selected by the current world config
+pub fn generator_kind() -> GeneratorKind {
+    GeneratorKind::parse(&crate::world::world_config_raw().generator).unwrap_or_default()
+}
+
+/// Block id by name, unknown blocks are air
+fn block(name: &str) -> u16 {
+    find_block(name).unwrap_or(0)
+}
+
+/// Superflat chunk
+pub fn generate_flat(pos: IVec3, config: &FlatConfig) -> Chunk {
+    let chunk = Chunk::empty();
+    let bottom = pos.y * SIZE_I32;
+
+    // World height -> block
+    let mut column = Vec::new();
+    for (name, thickness) in &config.layers {
+        column.extend(std::iter::repeat_n(block(name), *thickness as usize));
+    }
+
+    let mut raw = chunk.write();
+    for y in 0..SIZE_I32 {
+        let layer = bottom + y - config.base;
+        if layer < 0 { continue; }
+
+        let Some(id) = column.get(layer as usize).copied() else { break };
+        if id == 0 { continue; }
+
+        for z in 0..SIZE_I32 {
+            for x in 0..SIZE_I32 {
+                raw.set_block(RawChunk::block_index(IVec3::new(x, y, z)), id);
+            }
+        }
+    }
+
+    drop(raw);
+    chunk
+}
+
+/// Noise heightmap chunk, surface blocks are taken from biomes
+pub fn generate_terrain(pos: IVec3, config: &TerrainConfig) -> Chunk {
+    let chunk = Chunk::empty();
+    let bottom = pos.y * SIZE_I32;
+
+    let mut noise = Noise::new(crate::world::seed() as i32, NoiseType::OpenSimplex2, config.frequency);
+    noise.set_fractal(FractalType::FBm, config.octaves, 0.5);
+
+    let heights = noise.heightmap(pos, config.base, config.amplitude);
+    let biomes = super::biome_map(pos);
+    let (surface, filler) = (block(&config.surface), block(&config.filler));
+
+    let mut raw = chunk.write();
+    for z in 0..SIZE_I32 {
+        for x in 0..SIZE_I32 {
+            let column = (x + z * SIZE_I32) as usize;
+            let height = heights[column];
+
+            let (top, under) = match super::get_biome(biomes[column]) {
+                Some(biome) => (block(&biome.surface), block(&biome.filler)),
+                None => (surface, filler)
+            };
+
+            for y in 0..SIZE_I32 {
+                let world = bottom + y;
+                if world > height { break; }
+
+                let id = if world == height { top } else { under };
+                raw.set_block(RawChunk::block_index(IVec3::new(x, y, z)), id);
+            }
+        }
+    }
+
+    drop(raw);
+    chunk
+}
+
+/// Generate chunk with native generator of the current world
+pub fn generate_native(pos: IVec3) -> Chunk {
+    match generator_kind() {
+        GeneratorKind::Flat => generate_flat(pos, &crate::world::world_config_raw().flat()),
+        GeneratorKind::Script | GeneratorKind::Noise => generate_terrain(pos, &generators().terrain),
+    }
+}
+
+/// Generator scripts of the current world are not used
+pub fn is_native_forced() -> bool {
+    generator_kind() != GeneratorKind::Script
+}
+
+// ----------------------------------------------------------------------------------------------
+// Native generators functions
+
+#[rune::function]
+/// Create superflat chunk with layers of the world config
+pub fn flat_chunk(pos: &RnIVec3) -> Chunk {
+    generate_flat(pos.0, &crate::world::world_config_raw().flat())
+}
+
+#[rune::function]
+/// Create noise terrain chunk with configured parameters
+pub fn terrain_chunk(pos: &RnIVec3) -> Chunk {
+    generate_terrain(pos.0, &generators().terrain)
+}
@@ -0,0 +1,11 @@
+mod biome;
+mod carver;
+mod generators;
+mod noise;
+mod structure;
+
+pub use biome::*;
+pub use carver::*;
+pub use generators::*;
+pub use noise::*;
+pub use structure::*;
@@ -0,0 +1,194 @@
+use crate::noise::FastNoiseLite;
+use crate::chunk::*;
+use crate::math::*;
+
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseType {
+    #[rune(constructor)]
+    OpenSimplex2,
+    #[rune(constructor)]
+    OpenSimplex2S,
+    #[rune(constructor)]
+    Cellular,
+    #[rune(constructor)]
+    Perlin,
+    #[rune(constructor)]
+    ValueCubic,
+    #[rune(constructor)]
+    Value,
+}
+
+impl crate::api::ApiEnum for NoiseType {
+    const VARIANTS: &'static [Self] = &[
+        Self::OpenSimplex2, Self::OpenSimplex2S, Self::Cellular, Self::Perlin, Self::ValueCubic, Self::Value,
+    ];
+}
+
+impl From<NoiseType> for crate::noise::NoiseType {
+    fn from(value: NoiseType) -> Self {
+        match value {
+            NoiseType::OpenSimplex2 => Self::OpenSimplex2,
+            NoiseType::OpenSimplex2S => Self::OpenSimplex2S,
+            NoiseType::Cellular => Self::Cellular,
+            NoiseType::Perlin => Self::Perlin,
+            NoiseType::ValueCubic => Self::ValueCubic,
+            NoiseType::Value => Self::Value,
+        }
+    }
+}
+
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalType {
+    #[rune(constructor)]
+    None,
+    #[rune(constructor)]
+    FBm,
+    #[rune(constructor)]
+    Ridged,
+    #[rune(constructor)]
+    PingPong,
+}
+
+impl crate::api::ApiEnum for FractalType {
+    const VARIANTS: &'static [Self] = &[Self::None, Self::FBm, Self::Ridged, Self::PingPong];
+}
+
+impl From<FractalType> for crate::noise::FractalType {
+    fn from(value: FractalType) -> Self {
+        match value {
+            FractalType::None => Self::None,
+            FractalType::FBm => Self::FBm,
+            FractalType::Ridged => Self::Ridged,
+            FractalType::PingPong => Self::PingPong,
+        }
+    }
+}
+
+#[derive(rune::Any, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarpType {
+    #[rune(constructor)]
+    OpenSimplex2,
+    #[rune(constructor)]
+    OpenSimplex2Reduced,
+    #[rune(constructor)]
+    BasicGrid,
+}
+
+impl crate::api::ApiEnum for WarpType {
+    const VARIANTS: &'static [Self] = &[Self::OpenSimplex2, Self::OpenSimplex2Reduced, Self::BasicGrid];
+}
+
+impl From<WarpType> for crate::noise::DomainWarpType {
+    fn from(value: WarpType) -> Self {
+        match value {
+            WarpType::OpenSimplex2 => Self::OpenSimplex2,
+            WarpType::OpenSimplex2Reduced => Self::OpenSimplex2Reduced,
+            WarpType::BasicGrid => Self::BasicGrid,
+        }
+    }
+}
+
+/// Noise generator for world generation
+#[derive(rune::Any)]
+pub struct Noise {
+    inner: FastNoiseLite,
+
+    /// Is domain warp applied before sampling
+    warp: bool,
+}
+
+impl Noise {
+    pub fn new(seed: i32, ty: NoiseType, frequency: f32) -> Self {
+        let mut inner = FastNoiseLite::with_seed(seed);
+        inner.set_noise_type(Some(ty.into()));
+        inner.set_frequency(Some(frequency));
+
+        Self { inner, warp: false }
+    }
+
+    pub fn set_fractal(&mut self, ty: FractalType, octaves: i32, gain: f32) {
+        self.inner.set_fractal_type(Some(ty.into()));
+        self.inner.set_fractal_octaves(Some(octaves));
+        self.inner.set_fractal_gain(Some(gain));
+    }
+
+    pub fn set_warp(&mut self, ty: WarpType, amplitude: f32) {
+        self.inner.set_domain_warp_type(Some(ty.into()));
+        self.inner.set_domain_warp_amp(Some(amplitude));
+        self.warp = true;
+    }
+
+    /// Sample 2D noise in -1..1 range
+    pub fn sample2(&self, x: f32, y: f32) -> f32 {
+        let (x, y) = match self.warp {
+            true => self.inner.domain_warp_2d(x, y),
+            false => (x, y)
+        };
+
+        self.inner.get_noise_2d(x, y)
+    }
+
+    /// Sample 3D noise in -1..1 range
+    pub fn sample3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x, y, z) = match self.warp {
+            true => self.inner.domain_warp_3d(x, y, z),
+            false => (x, y, z)
+        };
+
+        self.inner.get_noise_3d(x, y, z)
+    }
+
+    /// World heights of chunk columns (index: x + z * SIZE)
+    pub fn heightmap(&self, pos: IVec3, base: i32, amplitude: f32) -> Vec<i32> {
+        let origin = pos * SIZE_I32;
+        let mut result = Vec::with_capacity(SIZE * SIZE);
+
+        for z in 0..SIZE_I32 {
+            for x in 0..SIZE_I32 {
+                let value = self.sample2((origin.x + x) as f32, (origin.z + z) as f32);
+                result.push(base + (value * amplitude).round() as i32);
+            }
+        }
+
+        result
+    }
+}
+
+#[rune::function]
+/// Create new noise generator
+pub fn new_noise(seed: i32, ty: NoiseType, frequency: f64) -> Noise {
+    Noise::new(seed, ty, frequency as f32)
+}
+
+#[rune::function(instance)]
+/// Set fractal type, octaves and gain
+pub fn fractal(noise: &mut Noise, ty: FractalType, octaves: i32, gain: f64) {
+    noise.set_fractal(ty, octaves, gain as f32)
+}
+
+#[rune::function(instance)]
+/// Enable domain warp before sampling
+pub fn domain_warp(noise: &mut Noise, ty: WarpType, amplitude: f64) {
+    noise.set_warp(ty, amplitude as f32)
+}
+
+#[rune::function(instance)]
+pub fn get2(noise: &Noise, x: f64, y: f64) -> f64 {
+    noise.sample2(x as f32, y as f32) as f64
+}
+
+#[rune::function(instance)]
+pub fn get3(noise: &Noise, x: f64, y: f64, z: f64) -> f64 {
+    noise.sample3(x as f32, y as f32, z as f32) as f64
+}
+
+#[rune::function(instance)]
+/// Get heights of all chunk columns (base + noise * amplitude) relative to chunk bottom
+pub fn fill_heightmap(noise: &Noise, pos: &RnIVec3, base: i32, amplitude: f64) -> Vec<i32> {
+    let bottom = pos.0.y * SIZE_I32;
+
+    noise.heightmap(pos.0, base, amplitude as f32)
+        .into_iter()
+        .map(|height| height - bottom)
+        .collect()
+}
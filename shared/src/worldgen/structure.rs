@@ -0,0 +1,207 @@
+//! Structure assets (`.structure`, JSON): block palette by name, size and blocks data.
+//! Structure parts in not generated chunks are placed when those chunks are added.
+
+use std::{sync::*, collections::*};
+use rune::runtime::VmResult;
+use crate::chunk::*;
+use crate::math::*;
+
+static STRUCTURES: OnceLock<Structures> = OnceLock::new();
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Structure {
+    /// File name by default
+    #[serde(default)]
+    pub name: String,
+    pub size: [i32; 3],
+
+    /// Block names, `null` keeps world block
+    pub palette: Vec<Option<String>>,
+    /// Palette indices, index: x + z * size.x + y * size.x * size.z
+    pub blocks: Vec<u16>,
+
+    /// Asset path
+    #[serde(skip)]
+    pub path: String,
+}
+
+impl Structure {
+    /// Parse and validate structure asset
+    pub fn parse(path: &str, value: &[u8]) -> Result<Self, String> {
+        let mut structure: Structure = serde_json::from_slice(value)
+            .map_err(|e| e.to_string())?;
+
+        // Positive and not larger than copied region
+        let volume = checked_volume(structure.size.map(i64::from))
+            .map_err(|_| format!("size {:?} must be positive and at most {} blocks", structure.size, MAX_REGION_VOLUME))?;
+
+        if structure.blocks.len() != volume {
+            return Err(format!("expected {} blocks, got {}", volume, structure.blocks.len()));
+        }
+
+        if let Some(i) = structure.blocks.iter().find(|i| **i as usize >= structure.palette.len()) {
+            return Err(format!("palette index {} is out of range", i));
+        }
+
+        if structure.name.is_empty() {
+            let file = path.rsplit('/').next().unwrap_or(path);
+            structure.name = file.split('.').next().unwrap_or(file).to_string();
+        }
+
+        structure.path = path.to_string();
+        Ok(structure)
+    }
+
+    pub fn size(&self) -> IVec3 {
+        IVec3::from_array(self.size)
+    }
+
+    /// World blocks of the placed structure: position and palette index
+    pub fn placed(&self, pos: IVec3, rotation: i32) -> Vec<(IVec3, u16)> {
+        let [sx, sy, sz] = self.size;
+        let mut result = Vec::with_capacity(self.blocks.len());
+
+        for y in 0..sy {
+            for z in 0..sz {
+                for x in 0..sx {
+                    // Quarter turns around Y, footprint keeps minimal corner at pos
+                    let (rx, rz) = match rotation.rem_euclid(4) {
+                        0 => (x, z),
+                        1 => (sz - 1 - z, x),
+                        2 => (sx - 1 - x, sz - 1 - z),
+                        _ => (z, sx - 1 - x),
+                    };
+
+                    let i = self.blocks[(x + z * sx + y * sx * sz) as usize];
+                    result.push((pos + IVec3::new(rx, y, rz), i));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Deferred blocks of the chunk: index and id
+type PendingBlocks = Vec<(usize, u16)>;
+
+#[derive(Debug)]
+struct Structures {
+    /// Structure name -> structure
+    values: RwLock<HashMap<String, Arc<Structure>>>,
+
+    /// World and chunk position -> deferred blocks
+    pending: Mutex<HashMap<(String, IVec3), PendingBlocks>>,
+}
+
+pub(crate) fn init_structures() {
+    let values = RwLock::new(HashMap::new());
+    let pending = Mutex::new(HashMap::new());
+
+    if STRUCTURES.set(Structures { values, pending }).is_err() {
+        log::error!("Already initialized");
+    }
+}
+
+/// Insert or replace structure asset
+pub fn insert_structure(path: &str, value: &[u8]) -> Result<(), String> {
+    let structure = Structure::parse(path, value)?;
+    remove_structure(path);
+
+    let structures = STRUCTURES.get().unwrap();
+    let mut guard = structures.values.write().unwrap();
+    guard.insert(structure.name.clone(), Arc::new(structure));
+
+    Ok(())
+}
+
+/// Remove structure by asset path
+pub fn remove_structure(path: &str) {
+    let structures = STRUCTURES.get().unwrap();
+    let mut guard = structures.values.write().unwrap();
+
+    guard.retain(|_, s| s.path != path);
+}
+
+pub fn get_structure(name: &str) -> Option<Arc<Structure>> {
+    let structures = STRUCTURES.get().unwrap();
+    structures.values.read().unwrap().get(name).cloned()
+}
+
+/// Place structure: existing chunks are written now, other parts are deferred
+pub fn place(name: &str, pos: IVec3, rotation: i32) -> Result<(), String> {
+    let structure = get_structure(name).ok_or_else(|| format!("Structure {} not found", name))?;
+
+    // Palette ids, None keeps world block
+    let mut palette = Vec::with_capacity(structure.palette.len());
+    for block in &structure.palette {
+        palette.push(match block {
+            Some(block) => Some(find_block(block).ok_or_else(|| format!("Block {} not found", block))?),
+            None => None
+        });
+    }
+
+    // Group blocks by chunks
+    let size = IVec3::splat(SIZE_I32);
+    let mut chunks: HashMap<IVec3, Vec<(usize, u16)>> = HashMap::new();
+
+    for (world, i) in structure.placed(pos, rotation) {
+        let Some(id) = palette[i as usize] else { continue };
+
+        let index = RawChunk::block_index(world.rem_euclid(size));
+        chunks.entry(world.div_euclid(size)).or_default().push((index, id));
+    }
+
+    let structures = STRUCTURES.get().unwrap();
+    let world = crate::current_world();
+
+    for (pos, blocks) in chunks {
+        match crate::_get_chunk(pos) {
+            Some(chunk) => write_blocks(&chunk, &blocks),
+            None => structures.pending.lock().unwrap().entry((world.clone(), pos)).or_default().extend(blocks)
+        }
+    }
+
+    Ok(())
+}
+
+fn write_blocks(chunk: &Chunk, blocks: &[(usize, u16)]) {
+    let (mut min, mut max) = (IVec3::MAX, IVec3::MIN);
+
+    let mut raw = chunk.write();
+    for (index, id) in blocks {
+        raw.set_block(*index, *id);
+
+        let local = RawChunk::block_position(*index);
+        (min, max) = (min.min(local), max.max(local));
+    }
+    drop(raw);
+
+    if !blocks.is_empty() {
+        chunk.changed(min, max);
+    }
+}
+
+/// Write deferred structures parts into the new chunk of the current world
+pub(crate) fn apply_pending(pos: IVec3, chunk: &Chunk) {
+    let structures = STRUCTURES.get().unwrap();
+    let key = (crate::current_world(), pos);
+    let Some(blocks) = structures.pending.lock().unwrap().remove(&key) else { return };
+
+    write_blocks(chunk, &blocks);
+}
+
+/// Drop deferred structures parts of the world
+pub(crate) fn clear_pending(world: &str) {
+    let structures = STRUCTURES.get().unwrap();
+    structures.pending.lock().unwrap().retain(|(w, _), _| w != world);
+}
+
+#[rune::function]
+/// Place structure by name with minimal corner at position and rotation (quarter turns)
+pub fn place_structure(name: String, pos: &RnIVec3, rotation: i32) -> VmResult<()> {
+    match place(&name, pos.0, rotation) {
+        Ok(()) => VmResult::Ok(()),
+        Err(e) => VmResult::panic(e)
+    }
+}
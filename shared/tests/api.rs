@@ -0,0 +1,38 @@
+use shared::api::Api;
+
+#[test]
+fn reference_lists_installed_items() {
+    let api = Api::new().expect("API error");
+
+    assert!(api.constants.contains(&(String::from("SIZE"), 16)));
+
+    let noise = api.types.iter().find(|t| t.path == "NoiseType").expect("No NoiseType");
+    assert_eq!(noise.variants, ["OpenSimplex2", "OpenSimplex2S", "Cellular", "Perlin", "ValueCubic", "Value"]);
+
+    let heightmap = api.functions.iter().find(|f| f.path == "Noise::fill_heightmap").expect("No fill_heightmap");
+    assert_eq!(heightmap.args, ["self", "pos", "base", "amplitude"]);
+    assert_eq!(heightmap.docs.len(), 1);
+
+    // Sorted for stable output
+    assert!(api.functions.windows(2).all(|w| w[0].path <= w[1].path));
+}
+
+#[test]
+fn json_and_markdown_output() {
+    let dir = std::env::temp_dir().join(format!("morph_api_{}", std::process::id()));
+    shared::api::generate(&dir).expect("API generation error");
+
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("morph.json")).unwrap()).unwrap();
+    let markdown = std::fs::read_to_string(dir.join("morph.md")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let ivec3 = json["functions"].as_array().unwrap().iter()
+        .find(|f| f["path"] == "ivec3")
+        .expect("No ivec3");
+    assert_eq!(ivec3["args"], serde_json::json!(["x", "y", "z"]));
+
+    assert!(markdown.starts_with("# Morph module API\n"));
+    assert!(markdown.contains("- `SIZE` = `16`"));
+    assert!(markdown.contains("- `NoiseType::Perlin`"));
+    assert!(markdown.contains("### `Noise::fill_heightmap(self, pos, base, amplitude)`"));
+}
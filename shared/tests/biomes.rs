@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use shared::math::IVec3;
+use shared::worldgen::*;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("biomes")
+}
+
+pub fn register() {
+    clear_biomes();
+
+    let cold = new_biome("snow", "dirt");
+    cold.temperature_max = 0.5;
+    add_biome("cold", cold);
+
+    let hot = new_biome("sand", "sand");
+    hot.temperature_min = 0.5;
+    add_biome("hot", hot);
+}
+
+pub fn corner() {
+    biome_at(48, -32)
+}
+"#;
+
+#[test]
+fn chunk_map_matches_column_sampler() {
+    shared::testing::setup();
+    shared::insert_script(String::from("biomes.rn"), SCRIPT).expect("Script error");
+    shared::testing::call::<()>("biomes.rn", "register").expect("Script error");
+
+    let pos = IVec3::new(3, 0, -2);
+    shared::in_world("biomes", || {
+        shared::testing::fill_chunk(pos, 0);
+        let map = shared::_get_biomes(pos).expect("No biomes map");
+        assert_eq!(map.len(), 16 * 16);
+
+        for (i, id) in map.iter().enumerate() {
+            let (x, z) = (pos.x * 16 + i as i32 % 16, pos.z * 16 + i as i32 / 16);
+            assert_eq!(*id, sample_biome(x, z));
+        }
+
+        // Column name is the same in scripts
+        let corner: Option<String> = shared::testing::call("biomes.rn", "corner").expect("Script error");
+        assert_eq!(corner, get_biome_name(map[0]));
+
+        // Climate selects both biomes over the world
+        let ids: HashSet<u16> = (0..64).map(|i| sample_biome(i * 250, i * -170)).collect();
+        assert_eq!(ids, HashSet::from([0, 1]));
+    });
+}
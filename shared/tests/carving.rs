@@ -0,0 +1,73 @@
+use shared::chunk::*;
+use shared::math::IVec3;
+use shared::worldgen::*;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    clear_blocks();
+    add_block("Air", None);
+    add_block("Stone", None);
+    add_block("Bedrock", None);
+    set_replaceable("Bedrock", false);
+
+    registry().world("carving")
+}
+"#;
+
+const STONE: u16 = 1;
+const BEDROCK: u16 = 2;
+
+fn filled(block: u16) -> Chunk {
+    let chunk = Chunk::empty();
+    let mut raw = chunk.write();
+    for i in 0..SIZE_P3 { raw.set_block(i, block); }
+    drop(raw);
+
+    chunk
+}
+
+fn count(chunk: &Chunk, block: u16) -> usize {
+    let raw = chunk.read();
+    (0..SIZE_P3).filter(|i| raw.get_block(*i) == block).count()
+}
+
+#[test]
+fn noise_caves_carve_replaceable_blocks_in_height_range() {
+    shared::testing::setup();
+    shared::insert_script(String::from("carving.rn"), SCRIPT).expect("Script error");
+
+    // Cheese threshold below noise range carves every block in range
+    let config = NoiseCaves { enabled: true, frequency: 0.03, cheese: -2.0, spaghetti: 0.0, min_y: 0, max_y: 7 };
+
+    shared::in_world("carving", || {
+        let stone = filled(STONE);
+        assert_eq!(carve_noise(IVec3::ZERO, &stone, &config), 16 * 16 * 8);
+        assert_eq!(count(&stone, 0), 16 * 16 * 8);
+        assert_eq!(stone.read().get_block(RawChunk::block_index(IVec3::new(0, 8, 0))), STONE);
+
+        let bedrock = filled(BEDROCK);
+        assert_eq!(carve_noise(IVec3::ZERO, &bedrock, &config), 0);
+        assert_eq!(carve_noise(IVec3::new(0, 1, 0), &filled(STONE), &config), 0);
+    });
+}
+
+#[test]
+fn worms_are_deterministic_and_cross_chunk_borders() {
+    shared::testing::setup();
+    shared::insert_script(String::from("carving.rn"), SCRIPT).expect("Script error");
+
+    // Worms start only in chunks at y = 0
+    let config = WormCaves { chance: 1.0, min_y: 0, max_y: 15, ..Default::default() };
+
+    shared::in_world("carving", || {
+        let below = IVec3::new(0, -1, 0);
+        let (a, b) = (filled(STONE), filled(STONE));
+
+        let carved = carve_worms(below, &a, &config);
+        assert!(carved > 0, "Neighbour worms don't reach the chunk");
+        assert_eq!(carve_worms(below, &b, &config), carved);
+        assert!(a.read().as_bytes() == b.read().as_bytes());
+
+        assert_eq!(carve_worms(below, &filled(BEDROCK), &config), 0);
+    });
+}
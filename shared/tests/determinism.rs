@@ -0,0 +1,31 @@
+use shared::math::IVec3;
+
+/// Generate default world by asset scripts, returns chunks data by position
+fn generate(seed: u64, ticks: usize) -> Vec<(IVec3, Vec<u8>)> {
+    shared::testing::reset_world(shared::DEFAULT_WORLD);
+    shared::set_deterministic(Some(seed));
+
+    for _ in 0..ticks {
+        shared::tick_scripts().expect("Tick error");
+    }
+
+    let mut positions = shared::in_world(shared::DEFAULT_WORLD, shared::chunk_positions);
+    positions.sort_by_key(|p| p.to_array());
+
+    positions.into_iter()
+        .filter_map(|pos| shared::in_world(shared::DEFAULT_WORLD, || shared::testing::chunk_bytes(pos)).map(|data| (pos, data)))
+        .collect()
+}
+
+#[test]
+fn same_seed_same_chunks() {
+    shared::testing::setup();
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/scripts");
+    shared::testing::load_scripts(dir).expect("Scripts load error");
+
+    let first = generate(42, 8);
+    let second = generate(42, 8);
+
+    assert!(!first.is_empty(), "No chunks generated");
+    assert_eq!(first, second);
+}
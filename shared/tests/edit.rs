@@ -0,0 +1,40 @@
+use shared::chunk::*;
+use shared::math::IVec3;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry()
+}
+
+pub fn copy_huge() {
+    copy_region(region(ivec3(-2147483648, 0, 0), ivec3(2147483647, 0, 0)))
+}
+
+pub fn copy_over_cap() {
+    copy_region(region(ivec3(0, 0, 0), ivec3(4096, 4096, 0)))
+}
+"#;
+
+#[test]
+fn region_volume_is_checked() {
+    assert_eq!(checked_volume([2, 3, 4]), Ok(24));
+    assert!(checked_volume([0, 1, 1]).is_err());
+    assert!(checked_volume([-2, -2, 1]).is_err());
+    assert!(checked_volume([i64::MAX, 2, 1]).is_err());
+    assert!(checked_volume([MAX_REGION_VOLUME as i64 + 1, 1, 1]).is_err());
+
+    assert!(Region::new(IVec3::MIN, IVec3::MAX).volume().is_err());
+
+    // Size must match data
+    let blocks = Blocks { size: IVec3::new(2, 2, 2), data: vec![0; 7] };
+    assert!(blocks.volume().is_err());
+}
+
+#[test]
+fn oversized_copy_is_script_error() {
+    shared::testing::setup();
+    shared::insert_script(String::from("edit.rn"), SCRIPT).expect("Script error");
+
+    assert!(shared::testing::call::<rune::Value>("edit.rn", "copy_huge").is_err());
+    assert!(shared::testing::call::<rune::Value>("edit.rn", "copy_over_cap").is_err());
+}
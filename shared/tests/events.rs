@@ -0,0 +1,37 @@
+use shared::events::Event;
+
+fn handler(world: &str) -> String {
+    format!(r#"
+pub fn init() {{
+    registry().world("{}").on("ping", "on_ping")
+}}
+
+pub fn on_ping(payload) {{
+    let count = store_get("count").unwrap_or(0);
+    store_set("count", count + payload)
+}}
+"#, world)
+}
+
+#[test]
+fn events_are_delivered_in_their_world() {
+    shared::testing::setup();
+    shared::insert_script(String::from("over.rn"), handler(shared::DEFAULT_WORLD)).expect("Script error");
+    shared::insert_script(String::from("nether.rn"), handler("nether")).expect("Script error");
+
+    // Broken payload doesn't drop the next events
+    shared::in_world(shared::DEFAULT_WORLD, || {
+        shared::events::emit(Event::Custom(String::from("ping"), String::from("{broken")));
+        shared::events::emit(Event::Custom(String::from("ping"), String::from("2")));
+    });
+    shared::tick_scripts().expect("Tick error");
+
+    assert_eq!(shared::store::get_raw("over.rn", "count").as_deref(), Some("2"));
+    assert_eq!(shared::store::get_raw("nether.rn", "count"), None);
+
+    shared::in_world("nether", || shared::events::emit(Event::Custom(String::from("ping"), String::from("5"))));
+    shared::tick_scripts().expect("Tick error");
+
+    assert_eq!(shared::store::get_raw("over.rn", "count").as_deref(), Some("2"));
+    assert_eq!(shared::store::get_raw("nether.rn", "count").as_deref(), Some("5"));
+}
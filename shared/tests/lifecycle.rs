@@ -0,0 +1,42 @@
+use shared::lifecycle::{self, ChunkState};
+use shared::math::IVec3;
+use shared::residency::StoredChunk;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry()
+}
+
+pub fn queued() {
+    let pos = ivec3(3, 0, 3);
+    queue_gen(pos);
+    chunk_state(pos) == Some(ChunkState::Queued) && chunk_state(pos) != Some(ChunkState::Generated)
+}
+"#;
+
+#[test]
+fn states_are_comparable_in_scripts() {
+    shared::testing::setup();
+    shared::insert_script(String::from("states.rn"), SCRIPT).expect("Script error");
+
+    assert!(shared::testing::call::<bool>("states.rn", "queued").expect("Script error"));
+}
+
+#[test]
+fn queued_chunk_is_restored() {
+    shared::testing::setup();
+    shared::testing::reset_world("restore");
+
+    shared::in_world("restore", || {
+        let pos = IVec3::new(1, 0, 1);
+        shared::push_gen(pos);
+        assert_eq!(lifecycle::get_record(pos).map(|r| r.state), Some(ChunkState::Queued));
+
+        // Storage load completes queued generation
+        let other = IVec3::new(2, 0, 2);
+        shared::testing::fill_chunk(other, 1);
+        let stored = shared::snapshot_chunk(other).expect("Chunk is resident");
+        assert!(shared::restore_chunk(StoredChunk { pos, ..stored }));
+        assert_eq!(lifecycle::get_record(pos).map(|r| r.state), Some(ChunkState::Generated));
+    });
+}
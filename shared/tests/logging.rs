@@ -0,0 +1,60 @@
+use std::sync::Mutex;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("logs")
+}
+
+pub fn spam() {
+    for i in 0..40 {
+        log_info(`record ${i}`);
+    }
+}
+
+pub fn once() {
+    log_warn("once")
+}
+"#;
+
+/// Captures records of the test script
+struct Capture(Mutex<Vec<String>>);
+
+impl log::Log for Capture {
+    fn enabled(&self, _: &log::Metadata) -> bool { true }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        if message.contains("logs.rn") {
+            self.0.lock().unwrap().push(message);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+fn take() -> Vec<String> {
+    std::mem::take(&mut *CAPTURE.0.lock().unwrap())
+}
+
+#[test]
+fn records_are_limited_per_script_and_tick() {
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    shared::testing::setup();
+    shared::insert_script(String::from("logs.rn"), SCRIPT).expect("Script error");
+    take();
+
+    shared::testing::call::<()>("logs.rn", "spam").expect("Script error");
+    let records = take();
+    assert_eq!(records.len(), 32);
+    assert_eq!(records[0], "[logs.rn:spam] record 0");
+    assert_eq!(records[31], "[logs.rn:spam] record 31");
+
+    // Limit is reset in the next tick with a note of dropped records
+    shared::tick_scripts().expect("Tick error");
+    shared::testing::call::<()>("logs.rn", "once").expect("Script error");
+    assert_eq!(take(), ["[logs.rn] 8 log records suppressed", "[logs.rn:once] once"]);
+}
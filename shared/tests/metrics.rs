@@ -0,0 +1,37 @@
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("metrics")
+}
+
+pub fn start() {
+    schedule_after(1, "ok", []);
+    schedule_after(1, "ok", []);
+    schedule_after(1, "fail", []);
+}
+
+pub fn ok() {}
+
+pub fn fail() {
+    panic("failed")
+}
+"#;
+
+#[test]
+fn scheduled_calls_are_counted_per_script() {
+    shared::testing::setup();
+    let path = String::from("metrics.rn");
+    shared::insert_script(path.clone(), SCRIPT).expect("Script error");
+
+    shared::testing::call::<()>("metrics.rn", "start").expect("Script error");
+    shared::tick_scripts().expect("Tick error");
+
+    let stats = shared::metrics::scripts_stats();
+    let script = stats.iter().find(|s| s.path == path).expect("No script metrics");
+    assert_eq!((script.invocations, script.errors), (3, 1));
+    assert!(script.min <= script.avg && script.avg <= script.max);
+    assert!(script.p99 <= script.max);
+
+    // Removed script has no metrics
+    shared::remove_script(&path);
+    assert!(shared::metrics::scripts_stats().iter().all(|s| s.path != path));
+}
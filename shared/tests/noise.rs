@@ -0,0 +1,56 @@
+use shared::math::IVec3;
+use shared::worldgen::*;
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("noise")
+}
+
+pub fn heights() {
+    let noise = new_noise(7, NoiseType::OpenSimplex2, 0.02);
+    noise.fractal(FractalType::FBm, 3, 0.5);
+
+    let pos = ivec3(1, 2, 3);
+    let heights = noise.fill_heightmap(pos, 40, 8.0);
+    heights.extend(noise.fill_heightmap(pos, 40, 8.0));
+    heights
+}
+"#;
+
+fn noise(seed: i32) -> Noise {
+    let mut noise = Noise::new(seed, NoiseType::OpenSimplex2, 0.02);
+    noise.set_fractal(FractalType::FBm, 3, 0.5);
+    noise
+}
+
+#[test]
+fn same_seed_same_samples() {
+    let (a, b, other) = (noise(7), noise(7), noise(8));
+    let points = (0..64).map(|i| (i as f32 * 3.7, i as f32 * -1.3));
+
+    let mut differs = false;
+    for (x, y) in points {
+        let value = a.sample2(x, y);
+        assert_eq!(value, b.sample2(x, y));
+        assert!((-1.0..=1.0).contains(&value));
+
+        differs |= value != other.sample2(x, y);
+    }
+
+    assert!(differs, "Seed doesn't change the noise");
+}
+
+#[test]
+fn heightmap_is_relative_to_chunk_bottom() {
+    shared::testing::setup();
+    shared::insert_script(String::from("noise.rn"), SCRIPT).expect("Script error");
+
+    let heights: Vec<i32> = shared::testing::call("noise.rn", "heights").expect("Script error");
+    let world = noise(7).heightmap(IVec3::new(1, 2, 3), 40, 8.0);
+    assert_eq!(world.len(), 16 * 16);
+
+    // Position is borrowed, same map is filled twice, 2 chunks above the world origin
+    let expected: Vec<i32> = world.iter().chain(&world).map(|h| h - 32).collect();
+    assert_eq!(heights, expected);
+    assert!(world.iter().all(|h| (32..=48).contains(h)));
+}
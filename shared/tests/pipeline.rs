@@ -0,0 +1,34 @@
+use shared::math::IVec3;
+use shared::pipeline::{self, Stage};
+
+#[test]
+fn stage_waits_for_all_neighbours() {
+    shared::testing::setup();
+    shared::testing::reset_world("pipeline");
+
+    shared::in_world("pipeline", || {
+        let corner = IVec3::ONE;
+        for y in -1..=1 {
+            for z in -1..=1 {
+                for x in -1..=1 {
+                    let pos = IVec3::new(x, y, z);
+                    if pos != corner { shared::testing::fill_chunk(pos, 1); }
+                }
+            }
+        }
+
+        // One of 26 neighbours is missing
+        pipeline::update();
+        assert_eq!(pipeline::get_stage(IVec3::ZERO), Some(Stage::Terrain));
+
+        shared::testing::fill_chunk(corner, 1);
+        pipeline::update();
+        assert_eq!(pipeline::get_stage(IVec3::ZERO), Some(Stage::Carving));
+
+        // Neighbours on the edge can't advance, so the centre waits for them
+        pipeline::update();
+        assert_eq!(pipeline::get_stage(IVec3::ZERO), Some(Stage::Carving));
+        assert_eq!(pipeline::get_stage(corner), Some(Stage::Terrain));
+        assert!(!pipeline::is_complete(IVec3::ZERO));
+    });
+}
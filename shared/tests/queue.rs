@@ -0,0 +1,56 @@
+use shared::math::IVec3;
+use shared::queue::WorkQueue;
+
+#[test]
+fn pop_by_distance_and_moved_centres() {
+    let mut queue = WorkQueue::default();
+    for x in [5, -2, 9, 0] {
+        queue.push(IVec3::new(x, 0, 0), false);
+    }
+
+    let centres = [IVec3::ZERO];
+    assert_eq!(queue.pop(0, &centres), Some(IVec3::new(0, 0, 0)));
+    assert_eq!(queue.pop(0, &centres), Some(IVec3::new(-2, 0, 0)));
+
+    // Queue is re-keyed by the new centre
+    let centres = [IVec3::new(10, 0, 0)];
+    assert_eq!(queue.pop(0, &centres), Some(IVec3::new(9, 0, 0)));
+
+    // Retried position waits for it's backoff
+    queue.retry(IVec3::new(9, 0, 0), 0);
+    assert_eq!(queue.pop(0, &centres), Some(IVec3::new(5, 0, 0)));
+    assert_eq!(queue.pop(0, &centres), None);
+    assert_eq!(queue.pop(2, &centres), Some(IVec3::new(9, 0, 0)));
+}
+
+#[test]
+fn backoff_cancel_and_lost_positions() {
+    let (a, b) = (IVec3::new(1, 0, 0), IVec3::new(2, 0, 0));
+    let mut queue = WorkQueue::default();
+
+    // Position is queued once
+    assert!(queue.push(a, false));
+    assert!(!queue.push(a, true));
+    assert!(queue.push(b, false));
+
+    // Delay doubles with every retry
+    assert_eq!(queue.pop(0, &[]), Some(a));
+    queue.retry(a, 0);
+    assert_eq!(queue.pop(2, &[]), Some(a));
+    queue.retry(a, 2);
+    assert_eq!(queue.pop(5, &[]), Some(b));
+    assert_eq!(queue.pop(5, &[]), None);
+    assert_eq!(queue.pop(6, &[]), Some(a));
+
+    // Taken position without result is dropped after timeout
+    assert_eq!(queue.retain(599, |_| false), Vec::<IVec3>::new());
+    assert_eq!(queue.retain(605, |_| false), [b]);
+    assert_eq!(queue.taken(), 1);
+    assert_eq!(queue.retain(700, |_| false), [a]);
+
+    // Not pinned positions are cancelled out of view
+    queue.push(a, true);
+    queue.push(b, false);
+    assert_eq!(queue.retain(700, |_| false), [b]);
+    assert!(queue.contains(a) && queue.is_pinned(a));
+}
@@ -0,0 +1,43 @@
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry()
+}
+
+pub fn extreme() {
+    let min = -9223372036854775807 - 1;
+    let max = 9223372036854775807;
+
+    let value = random_range(min, max);
+    value >= min && value < max
+}
+
+pub fn sample() {
+    let values = [];
+    for _ in 0..16 {
+        values.push(random_range(-50, 50));
+    }
+    values
+}
+"#;
+
+#[test]
+fn random_range_extremes_and_seed() {
+    shared::testing::setup();
+    shared::insert_script(String::from("random.rn"), SCRIPT).expect("Script error");
+
+    let in_range: bool = shared::testing::call("random.rn", "extreme").expect("Extreme range error");
+    assert!(in_range);
+
+    // Same seed restarts the same sequence
+    shared::random::set_seed(7);
+    let first: Vec<i64> = shared::testing::call("random.rn", "sample").expect("Sample error");
+    shared::random::set_seed(7);
+    let second: Vec<i64> = shared::testing::call("random.rn", "sample").expect("Sample error");
+
+    assert_eq!(first, second);
+    assert!(first.iter().all(|v| (-50..50).contains(v)));
+
+    shared::random::set_seed(8);
+    let other: Vec<i64> = shared::testing::call("random.rn", "sample").expect("Sample error");
+    assert_ne!(first, other);
+}
@@ -0,0 +1,29 @@
+use shared::math::IVec3;
+use shared::residency::StoredChunk;
+use shared::region_file::*;
+
+#[test]
+fn region_round_trip() {
+    let chunks = [IVec3::new(-1, 0, 31), IVec3::new(-32, 31, 0), IVec3::new(-5, 3, 0)].map(|pos| StoredChunk {
+        world: String::from("nether"),
+        pos,
+        data: (0..shared::chunk::BUF_SIZE).map(|i| (i as i32 + pos.x) as u8).collect(),
+        biomes: vec![pos.y as u16; shared::chunk::SIZE * shared::chunk::SIZE],
+        stage: 2,
+    });
+
+    let region = region_pos(chunks[0].pos);
+    assert_eq!(parse_file_name(&file_name(region)), Some(region));
+
+    let data = encode(region, &chunks).expect("Region encode error");
+    let mut decoded = decode("nether", region, &data).expect("Region decode error");
+    decoded.sort_by_key(|c| c.pos.to_array());
+
+    let mut expected = chunks.to_vec();
+    expected.sort_by_key(|c| c.pos.to_array());
+
+    for (a, b) in decoded.iter().zip(&expected) {
+        assert_eq!((&a.world, a.pos, &a.data, &a.biomes, a.stage), (&b.world, b.pos, &b.data, &b.biomes, b.stage));
+    }
+    assert_eq!(decoded.len(), expected.len());
+}
@@ -0,0 +1,71 @@
+use std::sync::Mutex;
+use shared::math::IVec3;
+use shared::residency::{self, StoredChunk};
+
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("budget")
+}
+
+pub fn pin() {
+    queue_gen(ivec3(0, 0, 0))
+}
+
+pub fn edit() {
+    fill_box(ivec3(48, 0, 0), ivec3(48, 0, 0), 2)
+}
+
+pub fn reload_evicted() {
+    get_chunk(current_world(), ivec3(1, 0, 0)).is_some()
+}
+
+pub fn reload_stored() {
+    get_chunk(current_world(), ivec3(2, 0, 0)).is_some()
+}
+"#;
+
+/// Storage of the test
+static STORAGE: Mutex<Vec<StoredChunk>> = Mutex::new(Vec::new());
+
+fn load(world: &str, pos: IVec3) -> Option<StoredChunk> {
+    STORAGE.lock().unwrap().iter().find(|s| s.world == world && s.pos == pos).cloned()
+}
+
+fn dirty() -> Vec<IVec3> {
+    residency::take_dirty().into_iter().map(|s| s.pos).collect()
+}
+
+#[test]
+fn budget_keeps_pinned_and_reloads_evicted() {
+    shared::testing::setup();
+    shared::testing::reset_world("budget");
+    shared::insert_script(String::from("budget.rn"), SCRIPT).expect("Script error");
+
+    let positions = [0, 1, 2, 3].map(|x| IVec3::new(x, 0, 0));
+    shared::in_world("budget", || {
+        for pos in positions { shared::testing::fill_chunk(pos, 1); }
+    });
+    shared::testing::call::<()>("budget.rn", "pin").expect("Script error");
+
+    // Least recently used are evicted, pinned one is kept
+    residency::set_budget(2 * shared::chunk::BUF_SIZE);
+    residency::enforce_budget();
+
+    let resident = || shared::in_world("budget", || positions.map(shared::has_chunk));
+    assert_eq!(resident(), [true, false, false, true]);
+
+    // Generated chunks are stored, edited one is stored again
+    assert_eq!(dirty().len(), 2);
+    shared::testing::call::<usize>("budget.rn", "edit").expect("Script error");
+    assert_eq!(dirty(), [positions[3]]);
+
+    // Evicted chunk isn't stored yet: it's taken back from the outbox
+    assert!(shared::testing::call::<bool>("budget.rn", "reload_evicted").expect("Script error"));
+    assert_eq!(dirty(), [positions[1]]);
+
+    // Stored chunk is read by the loader
+    STORAGE.lock().unwrap().extend(residency::take_stored());
+    residency::set_loader(load);
+    assert!(shared::testing::call::<bool>("budget.rn", "reload_stored").expect("Script error"));
+    assert_eq!(resident(), [true; 4]);
+}
@@ -0,0 +1,39 @@
+fn mesher() -> &'static str {
+    r#"
+pub fn init() {
+    meta("mesher", 1).role(Role::Mesher).world("roles")
+}
+
+pub fn mesher() {
+    store_set("ticks", store_get("ticks").unwrap_or(0) + 1)
+}
+"#
+}
+
+fn ticks(path: &str) -> Option<String> {
+    shared::store::get_raw(path, "ticks")
+}
+
+#[test]
+fn displaced_script_is_activated_after_unload() {
+    shared::testing::setup();
+    shared::set_deterministic(Some(1));
+
+    shared::insert_script(String::from("a.rn"), mesher()).expect("Script error");
+    shared::insert_script(String::from("b.rn"), mesher()).expect("Script error");
+
+    shared::tick_scripts().expect("Tick error");
+    assert_eq!(ticks("a.rn").as_deref(), Some("1"));
+    assert_eq!(ticks("b.rn"), None);
+
+    // Reloaded script keeps it's role
+    shared::insert_script(String::from("a.rn"), mesher()).expect("Script error");
+    shared::tick_scripts().expect("Tick error");
+    assert_eq!(ticks("a.rn").as_deref(), Some("2"));
+    assert_eq!(ticks("b.rn"), None);
+
+    // Displaced script takes the role
+    shared::remove_script(&String::from("a.rn"));
+    shared::tick_scripts().expect("Tick error");
+    assert_eq!(ticks("b.rn").as_deref(), Some("1"));
+}
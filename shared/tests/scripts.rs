@@ -0,0 +1,7 @@
+#[test]
+fn asset_scripts() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/scripts");
+    let report = shared::testing::run_dir(dir).expect("Scripts tests error");
+
+    assert!(report.is_success(), "{}", report);
+}
@@ -0,0 +1,30 @@
+use shared::lifecycle::{self, ChunkState};
+use shared::math::IVec3;
+use shared::world::{self, WorldConfig};
+
+fn state(x: i32) -> Option<ChunkState> {
+    lifecycle::get_record(IVec3::new(x, 0, 0)).map(|r| r.state)
+}
+
+#[test]
+fn view_distance_change_moves_streaming_radius() {
+    shared::testing::setup();
+    shared::testing::reset_world("stream");
+
+    shared::in_world("stream", || {
+        world::apply(WorldConfig { view_distance: 2, ..Default::default() }).expect("World config error");
+        shared::streaming::set_centre("anchor", IVec3::new(10, 0, 0), 3);
+
+        shared::streaming::update();
+        assert_eq!((state(2), state(3), state(13)), (Some(ChunkState::Queued), None, Some(ChunkState::Queued)));
+
+        // Spawn follows the view distance, anchor keeps it's own radius
+        world::apply(WorldConfig { view_distance: 1, ..Default::default() }).expect("World config error");
+        shared::streaming::update();
+
+        assert_eq!((state(1), state(2), state(13)), (Some(ChunkState::Queued), None, Some(ChunkState::Queued)));
+
+        let radius = shared::streaming::centres().into_iter().map(|(id, c)| (id, c.radius)).collect::<Vec<_>>();
+        assert_eq!(radius, [(String::from("anchor"), 3), (String::from(world::SPAWN_ANCHOR), 1)]);
+    });
+}
@@ -0,0 +1,21 @@
+use shared::worldgen::Structure;
+
+fn asset(size: &str, blocks: &str) -> Vec<u8> {
+    format!(r#"{{"size": {}, "palette": [null, "Stone"], "blocks": {}}}"#, size, blocks).into_bytes()
+}
+
+#[test]
+fn structure_size_is_checked() {
+    let structure = Structure::parse("structures/pillar.structure", &asset("[1, 2, 1]", "[1, 0]"))
+        .expect("Structure error");
+    assert_eq!(structure.name, "pillar");
+
+    // Size doesn't match blocks
+    assert!(Structure::parse("a.structure", &asset("[1, 2, 1]", "[1]")).is_err());
+    assert!(Structure::parse("a.structure", &asset("[0, 2, 1]", "[]")).is_err());
+    assert!(Structure::parse("a.structure", &asset("[-1, -2, 1]", "[1, 0]")).is_err());
+
+    // Overflowing and oversized sizes are errors, not panics
+    assert!(Structure::parse("a.structure", &asset("[2147483647, 2147483647, 2]", "[]")).is_err());
+    assert!(Structure::parse("a.structure", &asset("[65536, 65536, 1]", "[]")).is_err());
+}
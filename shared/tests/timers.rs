@@ -0,0 +1,47 @@
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry().world("timers")
+}
+
+pub fn start() {
+    schedule_after(2, "add", [5]);
+    schedule_every(3, "add_one");
+
+    let handle = schedule_after(1, "add", [100]);
+    cancel_schedule(handle) && !cancel_schedule(handle)
+}
+
+pub fn add(n) {
+    store_set("sum", store_get("sum").unwrap_or(0) + n)
+}
+
+pub fn add_one() {
+    add(1)
+}
+"#;
+
+fn sum_after(ticks: usize) -> Option<String> {
+    for _ in 0..ticks {
+        shared::tick_scripts().expect("Tick error");
+    }
+
+    shared::store::get_raw("timers.rn", "sum")
+}
+
+#[test]
+fn scheduled_calls_run_at_their_ticks() {
+    shared::testing::setup();
+    shared::insert_script(String::from("timers.rn"), SCRIPT).expect("Script error");
+
+    assert!(shared::testing::call::<bool>("timers.rn", "start").expect("Script error"));
+
+    assert_eq!(sum_after(1), None);
+    assert_eq!(sum_after(1).as_deref(), Some("5"));
+    assert_eq!(sum_after(1).as_deref(), Some("6"));
+    assert_eq!(sum_after(3).as_deref(), Some("7"));
+
+    // Repeated call of removed function is dropped by hot reload
+    let reloaded = SCRIPT.replace("pub fn add_one", "fn add_two");
+    shared::insert_script(String::from("timers.rn"), &reloaded).expect("Script error");
+    assert_eq!(sum_after(6).as_deref(), Some("7"));
+}
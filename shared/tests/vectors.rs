@@ -0,0 +1,50 @@
+const SCRIPT: &str = r#"
+pub fn init() {
+    registry()
+}
+
+pub fn add_overflow() {
+    ivec3(2147483647, 0, 0) + ivec3(1, 0, 0)
+}
+
+pub fn mul_overflow() {
+    ivec3(0, 65536, 0) * ivec3(1, 65536, 1)
+}
+
+pub fn div_zero() {
+    ivec3(1, 1, 1) / ivec3(1, 0, 1)
+}
+
+pub fn arithmetic() {
+    let p = ivec3(-3, 4, 5);
+    p += ivec3(1, 1, 1);
+    let q = (p - ivec3(0, 5, 0)) * ivec3(2, 2, 2) / ivec3(4, 4, 4);
+    [q.x, q.y, q.z]
+}
+
+pub fn reuse() {
+    let p = ivec3(7, -1, 2);
+    queue_gen(p);
+    chunk_state(p);
+    world_to_chunk(p);
+    [p.x, p.y, p.z]
+}
+"#;
+
+#[test]
+fn vectors_checked_and_reusable() {
+    shared::testing::setup();
+    shared::insert_script(String::from("vectors.rn"), SCRIPT).expect("Script error");
+
+    // Out of range operations are script errors, not host panics
+    for function in ["add_overflow", "mul_overflow", "div_zero"] {
+        assert!(shared::testing::call::<rune::Value>("vectors.rn", function).is_err(), "{} passed", function);
+    }
+
+    let result: Vec<i32> = shared::testing::call("vectors.rn", "arithmetic").expect("Arithmetic error");
+    assert_eq!(result, vec![-1, 0, 3]);
+
+    // Positions passed to native functions are still readable
+    let result: Vec<i32> = shared::testing::call("vectors.rn", "reuse").expect("Reuse error");
+    assert_eq!(result, vec![7, -1, 2]);
+}
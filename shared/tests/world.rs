@@ -0,0 +1,22 @@
+use shared::math::IVec3;
+use shared::world::{self, WorldConfig};
+
+#[test]
+fn flat_layers_are_set_by_world_config() {
+    shared::testing::setup();
+    shared::insert_script(String::from("blocks.rn"), include_str!("../../assets/scripts/blocks.rn")).expect("Script error");
+
+    let layers = vec![(String::from("Dirt"), 2), (String::from("Air"), 1), (String::from("Grass"), 1)];
+    let config = WorldConfig { generator: String::from("flat"), flat_base: 0, flat_layers: layers, ..Default::default() };
+    shared::in_world("flat", || world::apply(config)).expect("World config error");
+
+    let chunk = shared::in_world("flat", || shared::worldgen::generate_native(IVec3::ZERO));
+    let column = (0..5).map(|y| chunk.read().get_block(shared::chunk::RawChunk::block_index(IVec3::new(0, y, 0))));
+
+    let (dirt, grass) = (shared::chunk::find_block("Dirt").unwrap(), shared::chunk::find_block("Grass").unwrap());
+    assert_eq!(column.collect::<Vec<_>>(), [dirt, dirt, 0, grass, 0]);
+
+    // Too thick layers are rejected
+    let config = WorldConfig { flat_layers: vec![(String::from("Dirt"), u32::MAX), (String::from("Dirt"), 1)], ..Default::default() };
+    assert!(shared::in_world("flat", || world::apply(config)).is_err());
+}
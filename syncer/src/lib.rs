@@ -7,4 +7,6 @@ pub mod networking;
 
 pub const URI: &str = "http://localhost:3000";
 pub const MODULE: &str = "morph";
+/// Environment variable with the access token of admin identity (module owner)
+pub const TOKEN_VAR: &str = "MORPH_TOKEN";
 
@@ -41,15 +41,74 @@ impl AssetLoader for BlobAssetLoader {
     }
 }
 
+/// Region files tool: `import <world> <dir>` or `export <world>` arguments.
+/// Server accepts them from admin only, see `MORPH_TOKEN`
+#[derive(Resource, Debug, Clone, Default)]
+enum RegionTool {
+    #[default]
+    None,
+    /// World and directory of region files
+    Import(String, PathBuf),
+    Export(String),
+}
+
+impl RegionTool {
+    fn from_args() -> Self {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+        match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            ["import", world, dir] => Self::Import(world.to_string(), PathBuf::from(dir)),
+            ["export", world] => Self::Export(world.to_string()),
+            _ => Self::None
+        }
+    }
+}
+
 fn on_connected(
     mut messages: ReadStdbConnectedMessage,
     stdb: SpacetimeDB,
+    tool: Res<RegionTool>,
 ) {
     for _ in messages.read() {
         stdb.subscription_builder()
             .on_applied(|_| info!("Subscription applied"))
             .on_error(|_, err| error!("Subscription failed for: {}", err))
-            .subscribe(["SELECT * FROM assets", "SELECT * FROM scripts"]);
+            .subscribe(["SELECT * FROM assets", "SELECT * FROM scripts", "SELECT * FROM region_export"]);
+
+        match tool.as_ref() {
+            RegionTool::Import(world, dir) => import_regions(&stdb, world, dir),
+            RegionTool::Export(world) => stdb.reducers().export_world(world.clone()).unwrap(),
+            RegionTool::None => ()
+        }
+    }
+}
+
+/// Send region files of the directory into the world
+fn import_regions(stdb: &SpacetimeDB, world: &str, dir: &PathBuf) {
+    let entries = std::fs::read_dir(dir).expect("Regions directory read error");
+
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).map(String::from) else { continue };
+        if shared::region_file::parse_file_name(&name).is_none() { continue; }
+
+        let data = std::fs::read(&path).expect("Region file read error");
+
+        info!("Import: {}", name);
+        stdb.reducers().import_region(world.to_string(), name, data).unwrap();
+    }
+}
+
+/// Write exported region files into `regions` directory
+fn on_regions(
+    mut inserted: ReadInsertMessage<RegionExport>,
+    mut updated: ReadUpdateMessage<RegionExport>,
+) {
+    let rows = inserted.read().map(|m| &m.row).chain(updated.read().map(|m| &m.new));
+
+    for row in rows {
+        let path = PathBuf::from(format!("regions/{}", row.path));
+        std::fs::create_dir_all(path.parent().unwrap()).expect("Directory create error");
+        std::fs::write(&path, &row.data).expect("File write error");
     }
 }
 
@@ -131,9 +190,22 @@ fn reload_assets(
 
 /// Conntect to server, watch files, etc
 fn main() {
+    let mut stdb = StdbPlugin::default()
+        .with_uri(syncer::URI)
+        .with_module_name(syncer::MODULE)
+        .with_run_fn(DbConnection::run_threaded)
+        .add_table(RemoteTables::assets)
+        .add_table(RemoteTables::scripts)
+        .add_table(RemoteTables::region_export);
+
+    // Admin identity token, region tools are admin only
+    if let Ok(token) = std::env::var(syncer::TOKEN_VAR) {
+        stdb = stdb.with_token(token);
+    }
+
     App::new()
         .add_plugins((MinimalPlugins, bevy::log::LogPlugin::default()))
-        .add_systems(FixedPostUpdate, (on_connected, on_assets, reload_assets).chain())
+        .add_systems(FixedPostUpdate, (on_connected, on_assets, on_regions, reload_assets).chain())
         .add_plugins(AssetPlugin {
             file_path: "../assets".to_string(),
             ..default()
@@ -141,14 +213,8 @@ fn main() {
         .init_asset_loader::<BlobAssetLoader>()
         .init_asset::<Blob>()
         .init_resource::<AssetsHandler>()
-        .add_plugins(
-            StdbPlugin::default()
-                .with_uri(syncer::URI)
-                .with_module_name(syncer::MODULE)
-                .with_run_fn(DbConnection::run_threaded)
-                .add_table(RemoteTables::assets)
-                .add_table(RemoteTables::scripts)
-        )
+        .insert_resource(RegionTool::from_args())
+        .add_plugins(stdb)
         .run();
 }
 